@@ -1,27 +1,430 @@
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum NodeT {
     FUNCNode(FUNC),
+    ImportNode(Import),
+    ///`ret` inside a body
+    RetNode(Ret),
+    ///an expression used as a statement, the last one in a body is its value
+    ExprNode(Expr),
+    ///`while`, `loop` or `for`
+    LoopNode(Loop),
+    ///`break` or `continue`
+    JumpNode(Jump),
+    ///`let name: type = value`, `let mut` sets `dyna`
+    LetNode(Let),
+    ///`target = value` or `target += value`
+    AssignNode(Assign),
+    ///`const NAME: type = value;`, folded at compile time
+    ConstNode(Static),
+    ///`global NAME: type = value;`, module-level storage that can be assigned to
+    GlobalNode(Static),
 }
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum VarT {
     I32(i32),
     U8(u8),
     I64(i64),
     F32(f32),
     F64(f64),
+    Bool(bool),
+    ///the return type of fns that return nothing, also its only value
+    Nil,
 }
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(non_snake_case, unused)]
 pub struct Var {
     pub name: String,
+    ///declared `mut`, it can be assigned to
     pub dyna: bool,
     pub TT: VarT,
+    ///span of the name, or of the type for a return type
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
 }
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(non_snake_case, unused)]
 pub struct FUNC {
     pub name: String,
     pub args: Option<Vec<Var>>,
     pub body: Vec<NodeT>,
     pub ret: Var,
+    ///declared with `pub`, visible to modules importing this one
+    pub public: bool,
+    ///span of the name
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+///`import a.b;` -- a module path relative to the project root
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub path: Vec<String>,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+///`ret` or `ret <expr>`
+#[derive(Debug, Clone)]
+pub struct Ret {
+    pub value: Option<Expr>,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+///a local declared in a body, spanning the whole statement
+#[derive(Debug, Clone)]
+pub struct Let {
+    ///`dyna` is set for `let mut`
+    pub var: Var,
+    pub value: Expr,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+///a `const` or `global` at the top of a module, spanning the whole item
+#[derive(Debug, Clone)]
+pub struct Static {
+    pub var: Var,
+    pub value: Expr,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+///an assignment, spanning the whole statement
+#[derive(Debug, Clone)]
+pub struct Assign {
+    ///the name assigned to, an `ExprT::Ident`
+    pub target: Expr,
+    ///`Some` for compound assignments, `x += 1` is `x = x + 1`
+    pub op: Option<BinOp>,
+    pub value: Expr,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+///what repeats a loop body
+#[derive(Debug, Clone)]
+pub enum LoopT {
+    ///`while cond { .. }`
+    While(Expr),
+    ///`loop { .. }`, only left by `break` or `ret`
+    Forever,
+    ///`for var in from..to { .. }`, `to` excluded
+    For { var: Var, from: Expr, to: Expr },
+}
+
+///a loop, spanning its keyword
+#[derive(Debug, Clone)]
+pub struct Loop {
+    pub kind: LoopT,
+    pub body: Vec<NodeT>,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JumpT {
+    Break,
+    Continue,
+}
+
+///`break` or `continue`, leaving the innermost loop
+#[derive(Debug, Clone)]
+pub struct Jump {
+    pub kind: JumpT,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    ///`&&`, the right side only runs if the left is `true`
+    And,
+    ///`||`, the right side only runs if the left is `false`
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnOp {
+    Neg,
+    ///`!`, logical not
+    Not,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprT {
+    ///number literal as written, its type comes from the context
+    Num(String),
+    ///`true` or `false`
+    Bool(bool),
+    ///a param or local
+    Ident(String),
+    Unary(UnOp, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    ///`f(a, b)` or `util.f(a)`, a sip fn or a host fn
+    Call(Vec<String>, Vec<Expr>),
+    ///`x as i64`
+    Cast(Box<Expr>, VarT),
+    ///`if cond { .. } else { .. }`, `else if` is an `else` holding a single `if`
+    If(Box<Expr>, Vec<NodeT>, Option<Vec<NodeT>>),
+}
+
+///an expression and the span of its source
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprT,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl BinOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+            BinOp::Eq => "==",
+            BinOp::Ne => "!=",
+            BinOp::Lt => "<",
+            BinOp::Le => "<=",
+            BinOp::Gt => ">",
+            BinOp::Ge => ">=",
+            BinOp::And => "&&",
+            BinOp::Or => "||",
+        }
+    }
+
+    pub fn from_symbol(s: &str) -> Option<Self> {
+        match s {
+            "+" => Some(BinOp::Add),
+            "-" => Some(BinOp::Sub),
+            "*" => Some(BinOp::Mul),
+            "/" => Some(BinOp::Div),
+            "%" => Some(BinOp::Rem),
+            "==" => Some(BinOp::Eq),
+            "!=" => Some(BinOp::Ne),
+            "<" => Some(BinOp::Lt),
+            "<=" => Some(BinOp::Le),
+            ">" => Some(BinOp::Gt),
+            ">=" => Some(BinOp::Ge),
+            "&&" => Some(BinOp::And),
+            "||" => Some(BinOp::Or),
+            _ => None,
+        }
+    }
+
+    ///binding strength, higher binds tighter
+    pub fn prec(self) -> u8 {
+        match self {
+            BinOp::Or => 1,
+            BinOp::And => 2,
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => 3,
+            BinOp::Add | BinOp::Sub => 4,
+            BinOp::Mul | BinOp::Div | BinOp::Rem => 5,
+        }
+    }
+
+    ///`+ - * / %`, numbers in and a number of the same type out
+    pub fn is_arith(self) -> bool {
+        matches!(self, BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Rem)
+    }
+
+    ///`== != < <= > >=`, two values of one type in and a `bool` out
+    pub fn is_cmp(self) -> bool {
+        matches!(self, BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge)
+    }
+}
+
+impl UnOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnOp::Neg => "-",
+            UnOp::Not => "!",
+        }
+    }
+
+    pub fn from_symbol(s: &str) -> Option<Self> {
+        match s {
+            "-" => Some(UnOp::Neg),
+            "!" => Some(UnOp::Not),
+            _ => None,
+        }
+    }
+}
+
+impl Expr {
+    ///a literal whose type is still open, e.g. `1` or `-(2 * 3)`
+    pub fn is_untyped(&self) -> bool {
+        match &self.kind {
+            ExprT::Num(_) => true,
+            ExprT::Unary(_, e) => e.is_untyped(),
+            // a comparison of literals is still a `bool`
+            ExprT::Binary(op, l, r) => op.is_arith() && l.is_untyped() && r.is_untyped(),
+            ExprT::If(_, then, Some(els)) => [then, els].iter().all(|b| tail(b).is_some_and(Expr::is_untyped)),
+            _ => false,
+        }
+    }
+}
+
+///the value of a block, its last statement if that is an expression
+pub fn tail(body: &[NodeT]) -> Option<&Expr> {
+    match body.last() {
+        Some(NodeT::ExprNode(e)) => Some(e),
+        _ => None,
+    }
+}
+
+impl JumpT {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            JumpT::Break => "break",
+            JumpT::Continue => "continue",
+        }
+    }
+}
+
+///`{ a; b }` on one line
+fn block(body: &[NodeT]) -> String {
+    let stmts: Vec<String> = body.iter().map(|n| n.to_string()).collect();
+    if stmts.is_empty() { "{}".into() } else { format!("{{ {} }}", stmts.join("; ")) }
+}
+
+impl std::fmt::Display for LoopT {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoopT::While(c) => write!(f, "while {}", c),
+            LoopT::Forever => write!(f, "loop"),
+            LoopT::For { var, from, to } => write!(f, "for {} in {}..{}", var.name, from, to),
+        }
+    }
+}
+
+///statements as source-like text, declarations only by name
+impl std::fmt::Display for NodeT {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeT::FUNCNode(func) => write!(f, "{}", func.signature()),
+            NodeT::ImportNode(i) => write!(f, "import {}", i.path.join(".")),
+            NodeT::RetNode(r) => match &r.value {
+                Some(v) => write!(f, "ret {}", v),
+                None => write!(f, "ret"),
+            },
+            NodeT::ExprNode(e) => write!(f, "{}", e),
+            NodeT::LoopNode(l) => write!(f, "{} {}", l.kind, block(&l.body)),
+            NodeT::JumpNode(j) => write!(f, "{}", j.kind.keyword()),
+            NodeT::LetNode(l) => {
+                write!(f, "let {}{}: {} = {}", if l.var.dyna { "mut " } else { "" }, l.var.name, l.var.TT.name(), l.value)
+            }
+            NodeT::AssignNode(a) => write!(f, "{} {}= {}", a.target, a.op.map_or("", BinOp::symbol), a.value),
+            NodeT::ConstNode(s) => write!(f, "const {}: {} = {}", s.var.name, s.var.TT.name(), s.value),
+            NodeT::GlobalNode(s) => write!(f, "global {}: {} = {}", s.var.name, s.var.TT.name(), s.value),
+        }
+    }
+}
+
+///source-like text, nested operators get parentheses
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nested = |e: &Expr| match e.kind {
+            ExprT::Binary(..) | ExprT::If(..) => format!("({})", e),
+            _ => e.to_string(),
+        };
+        match &self.kind {
+            ExprT::Num(n) => write!(f, "{}", n),
+            ExprT::Bool(b) => write!(f, "{}", b),
+            ExprT::Ident(n) => write!(f, "{}", n),
+            ExprT::Unary(op, e) => write!(f, "{}{}", op.symbol(), nested(e)),
+            ExprT::Binary(op, l, r) => write!(f, "{} {} {}", nested(l), op.symbol(), nested(r)),
+            ExprT::Call(path, args) => {
+                let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}({})", path.join("."), args.join(", "))
+            }
+            ExprT::Cast(e, ty) => write!(f, "{} as {}", nested(e), ty.name()),
+            ExprT::If(c, then, els) => {
+                write!(f, "if {} {}", c, block(then))?;
+                match els {
+                    Some(els) => write!(f, " else {}", block(els)),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl VarT {
+    ///the sip spelling of the type
+    pub fn name(&self) -> &'static str {
+        match self {
+            VarT::I32(_) => "i32",
+            VarT::U8(_) => "u8",
+            VarT::I64(_) => "i64",
+            VarT::F32(_) => "f32",
+            VarT::F64(_) => "f64",
+            VarT::Bool(_) => "bool",
+            VarT::Nil => "nil",
+        }
+    }
+
+    ///zero value of the type called `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "i32" => Some(VarT::I32(0)),
+            "u8" => Some(VarT::U8(0)),
+            "i64" => Some(VarT::I64(0)),
+            "f32" => Some(VarT::F32(0.0)),
+            "f64" => Some(VarT::F64(0.0)),
+            "bool" => Some(VarT::Bool(false)),
+            "nil" => Some(VarT::Nil),
+            _ => None,
+        }
+    }
+
+    ///same type, whatever the value
+    pub fn same_type(&self, other: &VarT) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl std::fmt::Display for VarT {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VarT::I32(v) => write!(f, "{}", v),
+            VarT::U8(v) => write!(f, "{}", v),
+            VarT::I64(v) => write!(f, "{}", v),
+            VarT::F32(v) => write!(f, "{}", v),
+            VarT::F64(v) => write!(f, "{}", v),
+            VarT::Bool(v) => write!(f, "{}", v),
+            VarT::Nil => write!(f, "nil"),
+        }
+    }
+}
+
+impl FUNC {
+    ///`pub fn name(i32 a, u8 b) @ i64`, as it would be written
+    pub fn signature(&self) -> String {
+        let args: Vec<String> = self.args.iter().flatten().map(|a| format!("{} {}", a.TT.name(), a.name)).collect();
+        format!("{}fn {}({}) @ {}", if self.public { "pub " } else { "" }, self.name, args.join(", "), self.ret.TT.name())
+    }
 }
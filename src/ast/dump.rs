@@ -0,0 +1,69 @@
+use super::defs::{ExprT, NodeT, Var};
+use crate::term::glyphs;
+
+///a label and the children below it
+struct Tree {
+    label: String,
+    kids: Vec<Tree>,
+}
+
+fn leaf(label: String) -> Tree {
+    Tree { label, kids: Vec::new() }
+}
+
+fn var(kind: &str, v: &Var) -> Tree {
+    leaf(format!("{} {} {}{}", kind, v.TT.name(), v.name, if v.dyna { " (mut)" } else { "" }))
+}
+
+fn block(label: &str, body: &[NodeT]) -> Tree {
+    Tree { label: label.into(), kids: body.iter().map(node).collect() }
+}
+
+fn node(n: &NodeT) -> Tree {
+    match n {
+        NodeT::FUNCNode(f) => {
+            let mut kids: Vec<Tree> = f.args.iter().flatten().map(|a| var("param", a)).collect();
+            kids.push(leaf(format!("ret {}", f.ret.TT.name())));
+            if !f.body.is_empty() {
+                kids.push(block("body", &f.body));
+            }
+            Tree { label: format!("{}fn {} [line {}]", if f.public { "pub " } else { "" }, f.name, f.line), kids }
+        }
+        NodeT::ImportNode(i) => leaf(format!("import {} [line {}]", i.path.join("."), i.line)),
+        NodeT::RetNode(r) => match &r.value {
+            Some(v) => leaf(format!("ret {} [line {}]", v, r.line)),
+            None => leaf(format!("ret [line {}]", r.line)),
+        },
+        NodeT::ExprNode(e) => match &e.kind {
+            ExprT::If(c, then, els) => {
+                let mut kids = vec![block("then", then)];
+                kids.extend(els.as_deref().map(|b| block("else", b)));
+                Tree { label: format!("if {} [line {}]", c, e.line), kids }
+            }
+            _ => leaf(format!("{} [line {}]", e, e.line)),
+        },
+        NodeT::LoopNode(l) => Tree { label: format!("{} [line {}]", l.kind, l.line), kids: l.body.iter().map(node).collect() },
+        NodeT::JumpNode(j) => leaf(format!("{} [line {}]", j.kind.keyword(), j.line)),
+        NodeT::LetNode(l) => Tree { label: format!("let [line {}]", l.line), kids: vec![var("var", &l.var), leaf(format!("= {}", l.value))] },
+        NodeT::AssignNode(a) => leaf(format!("{} [line {}]", n, a.line)),
+        NodeT::ConstNode(s) | NodeT::GlobalNode(s) => leaf(format!("{} [line {}]", n, s.line)),
+    }
+}
+
+fn draw(t: &Tree, prefix: &str, last: bool, out: &mut String) {
+    let g = glyphs();
+    out.push_str(&format!("{}{} {}\n", prefix, if last { g.end } else { g.tee }, t.label));
+    let prefix = if last { format!("{}   ", prefix) } else { format!("{}{}  ", prefix, g.bar) };
+    for (i, k) in t.kids.iter().enumerate() {
+        draw(k, &prefix, i + 1 == t.kids.len(), out);
+    }
+}
+
+///`nodes` as an indented tree, one node per line
+pub fn tree(nodes: &[NodeT]) -> String {
+    let mut out = String::new();
+    for (i, n) in nodes.iter().enumerate() {
+        draw(&node(n), "", i + 1 == nodes.len(), &mut out);
+    }
+    out
+}
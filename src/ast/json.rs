@@ -0,0 +1,281 @@
+use super::defs::{Assign, BinOp, Expr, ExprT, FUNC, Import, Jump, JumpT, Let, Loop, LoopT, NodeT, Ret, Static, UnOp, Var, VarT};
+use crate::json::Json;
+
+fn num(n: usize) -> Json {
+    Json::Num(n as f64)
+}
+
+fn var_to_json(v: &Var) -> Json {
+    Json::obj([
+        ("name", Json::str(v.name.clone())),
+        ("dyna", Json::Bool(v.dyna)),
+        ("type", Json::str(v.TT.name())),
+        ("line", num(v.line)),
+        ("start", num(v.start)),
+        ("end", num(v.end)),
+    ])
+}
+
+fn var_from_json(j: &Json) -> Option<Var> {
+    Some(Var {
+        name: j.get("name")?.as_str()?.to_string(),
+        dyna: j.get("dyna")?.as_bool()?,
+        TT: VarT::from_name(j.get("type")?.as_str()?)?,
+        line: j.get("line")?.as_usize()?,
+        start: j.get("start")?.as_usize()?,
+        end: j.get("end")?.as_usize()?,
+    })
+}
+
+fn strs(path: &[String]) -> Json {
+    Json::Arr(path.iter().map(|s| Json::str(s.clone())).collect())
+}
+
+fn strs_from(j: &Json) -> Option<Vec<String>> {
+    j.as_arr()?.iter().map(|s| s.as_str().map(str::to_string)).collect()
+}
+
+///`(line, start, end)` of a node or expression
+fn span(j: &Json) -> Option<(usize, usize, usize)> {
+    Some((j.get("line")?.as_usize()?, j.get("start")?.as_usize()?, j.get("end")?.as_usize()?))
+}
+
+///an expression as JSON, tagged by `kind`
+pub fn expr_to_json(e: &Expr) -> Json {
+    let mut pairs = match &e.kind {
+        ExprT::Num(n) => vec![("kind", Json::str("num")), ("value", Json::str(n.clone()))],
+        ExprT::Bool(b) => vec![("kind", Json::str("bool")), ("value", Json::Bool(*b))],
+        ExprT::Ident(n) => vec![("kind", Json::str("ident")), ("name", Json::str(n.clone()))],
+        ExprT::Unary(op, x) => vec![
+            ("kind", Json::str("unary")),
+            ("op", Json::str(op.symbol())),
+            ("expr", expr_to_json(x)),
+        ],
+        ExprT::Binary(op, l, r) => vec![
+            ("kind", Json::str("binary")),
+            ("op", Json::str(op.symbol())),
+            ("lhs", expr_to_json(l)),
+            ("rhs", expr_to_json(r)),
+        ],
+        ExprT::Call(path, args) => vec![
+            ("kind", Json::str("call")),
+            ("path", strs(path)),
+            ("args", Json::Arr(args.iter().map(expr_to_json).collect())),
+        ],
+        ExprT::Cast(x, ty) => vec![
+            ("kind", Json::str("cast")),
+            ("expr", expr_to_json(x)),
+            ("type", Json::str(ty.name())),
+        ],
+        ExprT::If(c, then, els) => vec![
+            ("kind", Json::str("if")),
+            ("cond", expr_to_json(c)),
+            ("then", nodes_to_json(then)),
+            ("else", els.as_deref().map_or(Json::Null, nodes_to_json)),
+        ],
+    };
+    pairs.extend([("line", num(e.line)), ("start", num(e.start)), ("end", num(e.end))]);
+    Json::obj(pairs)
+}
+
+pub fn expr_from_json(j: &Json) -> Option<Expr> {
+    let sub = |k: &str| expr_from_json(j.get(k)?).map(Box::new);
+    let kind = match j.get("kind")?.as_str()? {
+        "num" => ExprT::Num(j.get("value")?.as_str()?.to_string()),
+        "bool" => ExprT::Bool(j.get("value")?.as_bool()?),
+        "ident" => ExprT::Ident(j.get("name")?.as_str()?.to_string()),
+        "unary" => ExprT::Unary(UnOp::from_symbol(j.get("op")?.as_str()?)?, sub("expr")?),
+        "binary" => ExprT::Binary(BinOp::from_symbol(j.get("op")?.as_str()?)?, sub("lhs")?, sub("rhs")?),
+        "call" => ExprT::Call(
+            strs_from(j.get("path")?)?,
+            j.get("args")?.as_arr()?.iter().map(expr_from_json).collect::<Option<_>>()?,
+        ),
+        "cast" => ExprT::Cast(sub("expr")?, VarT::from_name(j.get("type")?.as_str()?)?),
+        "if" => ExprT::If(
+            sub("cond")?,
+            nodes_from_json(j.get("then")?)?,
+            match j.get("else")? {
+                Json::Null => None,
+                b => Some(nodes_from_json(b)?),
+            },
+        ),
+        _ => return None,
+    };
+    let (line, start, end) = span(j)?;
+    Some(Expr { kind, line, start, end })
+}
+
+///a node as JSON, tagged by `kind`
+pub fn node_to_json(n: &NodeT) -> Json {
+    match n {
+        NodeT::FUNCNode(f) => Json::obj([
+            ("kind", Json::str("fn")),
+            ("name", Json::str(f.name.clone())),
+            ("public", Json::Bool(f.public)),
+            ("args", match &f.args {
+                Some(args) => Json::Arr(args.iter().map(var_to_json).collect()),
+                None => Json::Null,
+            }),
+            ("ret", var_to_json(&f.ret)),
+            ("body", Json::Arr(f.body.iter().map(node_to_json).collect())),
+            ("line", num(f.line)),
+            ("start", num(f.start)),
+            ("end", num(f.end)),
+        ]),
+        NodeT::ImportNode(i) => Json::obj([
+            ("kind", Json::str("import")),
+            ("path", strs(&i.path)),
+            ("line", num(i.line)),
+            ("start", num(i.start)),
+            ("end", num(i.end)),
+        ]),
+        NodeT::RetNode(r) => Json::obj([
+            ("kind", Json::str("ret")),
+            ("value", r.value.as_ref().map_or(Json::Null, expr_to_json)),
+            ("line", num(r.line)),
+            ("start", num(r.start)),
+            ("end", num(r.end)),
+        ]),
+        NodeT::ExprNode(e) => Json::obj([
+            ("kind", Json::str("expr")),
+            ("expr", expr_to_json(e)),
+        ]),
+        NodeT::LoopNode(l) => {
+            let mut fields = vec![("kind", Json::str("loop"))];
+            match &l.kind {
+                LoopT::While(c) => fields.extend([("loop", Json::str("while")), ("cond", expr_to_json(c))]),
+                LoopT::Forever => fields.push(("loop", Json::str("loop"))),
+                LoopT::For { var, from, to } => fields.extend([
+                    ("loop", Json::str("for")),
+                    ("var", var_to_json(var)),
+                    ("from", expr_to_json(from)),
+                    ("to", expr_to_json(to)),
+                ]),
+            }
+            fields.extend([
+                ("body", nodes_to_json(&l.body)),
+                ("line", num(l.line)),
+                ("start", num(l.start)),
+                ("end", num(l.end)),
+            ]);
+            Json::obj(fields)
+        }
+        NodeT::LetNode(l) => Json::obj([
+            ("kind", Json::str("let")),
+            ("var", var_to_json(&l.var)),
+            ("value", expr_to_json(&l.value)),
+            ("line", num(l.line)),
+            ("start", num(l.start)),
+            ("end", num(l.end)),
+        ]),
+        NodeT::AssignNode(a) => Json::obj([
+            ("kind", Json::str("assign")),
+            ("target", expr_to_json(&a.target)),
+            ("op", a.op.map_or(Json::Null, |op| Json::str(op.symbol()))),
+            ("value", expr_to_json(&a.value)),
+            ("line", num(a.line)),
+            ("start", num(a.start)),
+            ("end", num(a.end)),
+        ]),
+        NodeT::ConstNode(st) | NodeT::GlobalNode(st) => Json::obj([
+            ("kind", Json::str(if matches!(n, NodeT::ConstNode(_)) { "const" } else { "global" })),
+            ("var", var_to_json(&st.var)),
+            ("value", expr_to_json(&st.value)),
+            ("line", num(st.line)),
+            ("start", num(st.start)),
+            ("end", num(st.end)),
+        ]),
+        NodeT::JumpNode(jmp) => Json::obj([
+            ("kind", Json::str(jmp.kind.keyword())),
+            ("line", num(jmp.line)),
+            ("start", num(jmp.start)),
+            ("end", num(jmp.end)),
+        ]),
+    }
+}
+
+///inverse of `node_to_json`, `None` if the JSON is not a node
+pub fn node_from_json(j: &Json) -> Option<NodeT> {
+    match j.get("kind")?.as_str()? {
+        "fn" => Some(NodeT::FUNCNode(FUNC {
+            name: j.get("name")?.as_str()?.to_string(),
+            public: j.get("public")?.as_bool()?,
+            args: match j.get("args")? {
+                Json::Null => None,
+                a => Some(a.as_arr()?.iter().map(var_from_json).collect::<Option<_>>()?),
+            },
+            ret: var_from_json(j.get("ret")?)?,
+            body: nodes_from_json(j.get("body")?)?,
+            line: j.get("line")?.as_usize()?,
+            start: j.get("start")?.as_usize()?,
+            end: j.get("end")?.as_usize()?,
+        })),
+        "import" => Some(NodeT::ImportNode(Import {
+            path: strs_from(j.get("path")?)?,
+            line: j.get("line")?.as_usize()?,
+            start: j.get("start")?.as_usize()?,
+            end: j.get("end")?.as_usize()?,
+        })),
+        "ret" => {
+            let (line, start, end) = span(j)?;
+            let value = match j.get("value")? {
+                Json::Null => None,
+                v => Some(expr_from_json(v)?),
+            };
+            Some(NodeT::RetNode(Ret { value, line, start, end }))
+        }
+        "expr" => Some(NodeT::ExprNode(expr_from_json(j.get("expr")?)?)),
+        "loop" => {
+            let (line, start, end) = span(j)?;
+            let kind = match j.get("loop")?.as_str()? {
+                "while" => LoopT::While(expr_from_json(j.get("cond")?)?),
+                "loop" => LoopT::Forever,
+                "for" => LoopT::For {
+                    var: var_from_json(j.get("var")?)?,
+                    from: expr_from_json(j.get("from")?)?,
+                    to: expr_from_json(j.get("to")?)?,
+                },
+                _ => return None,
+            };
+            Some(NodeT::LoopNode(Loop { kind, body: nodes_from_json(j.get("body")?)?, line, start, end }))
+        }
+        "let" => {
+            let (line, start, end) = span(j)?;
+            Some(NodeT::LetNode(Let { var: var_from_json(j.get("var")?)?, value: expr_from_json(j.get("value")?)?, line, start, end }))
+        }
+        "assign" => {
+            let (line, start, end) = span(j)?;
+            let op = match j.get("op")? {
+                Json::Null => None,
+                op => Some(BinOp::from_symbol(op.as_str()?)?),
+            };
+            Some(NodeT::AssignNode(Assign {
+                target: expr_from_json(j.get("target")?)?,
+                op,
+                value: expr_from_json(j.get("value")?)?,
+                line,
+                start,
+                end,
+            }))
+        }
+        kw @ ("const" | "global") => {
+            let (line, start, end) = span(j)?;
+            let st = Static { var: var_from_json(j.get("var")?)?, value: expr_from_json(j.get("value")?)?, line, start, end };
+            Some(if kw == "const" { NodeT::ConstNode(st) } else { NodeT::GlobalNode(st) })
+        }
+        kw @ ("break" | "continue") => {
+            let (line, start, end) = span(j)?;
+            let kind = if kw == "break" { JumpT::Break } else { JumpT::Continue };
+            Some(NodeT::JumpNode(Jump { kind, line, start, end }))
+        }
+        _ => None,
+    }
+}
+
+pub fn nodes_to_json(nodes: &[NodeT]) -> Json {
+    Json::Arr(nodes.iter().map(node_to_json).collect())
+}
+
+pub fn nodes_from_json(j: &Json) -> Option<Vec<NodeT>> {
+    j.as_arr()?.iter().map(node_from_json).collect()
+}
@@ -1 +1,3 @@
-pub mod defs;
\ No newline at end of file
+pub mod defs;
+pub mod json;
+pub mod dump;
@@ -0,0 +1,160 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ast::{defs::NodeT, json::{nodes_from_json, nodes_to_json}},
+    json::Json,
+};
+
+///cache directory relative to the project root
+pub const CACHE_DIR: &str = "target/sip-cache";
+///bumped whenever the ast JSON changes shape (a new node, field or expression kind),
+///part of the key of every entry so older entries are ignored
+pub const FORMAT: usize = 7;
+
+///FNV-1a, stable across runs and platforms unlike `DefaultHasher`
+pub fn fnv(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+///hash of everything other modules can see: the signatures of the `pub` fns
+pub fn sig_hash(ast: &[NodeT]) -> u64 {
+    let mut sigs: Vec<String> = ast.iter().filter_map(|n| match n {
+        NodeT::FUNCNode(f) if f.public => Some(f.signature()),
+        _ => None,
+    }).collect();
+    sigs.sort_unstable();
+    fnv(sigs.join(";").as_bytes())
+}
+
+///what is cached for a single file
+pub struct Entry {
+    ///content hash of the source
+    pub hash: u64,
+    pub ast: Vec<NodeT>,
+    ///`check_key` of the file when it last type checked without a single diagnostic
+    pub checked: Option<u64>,
+}
+
+///key of an entry: the content hash together with the compiler version and `FORMAT`
+///
+/// an entry written for the same source by another compiler, or before the ast
+/// changed shape, never matches
+pub fn key(hash: u64) -> u64 {
+    fnv(format!("{}/{}/{:016x}", env!("CARGO_PKG_VERSION"), FORMAT, hash).as_bytes())
+}
+
+///key of a type check: `key` of the source with the `sig_hash` of every module it imports, in import order
+///
+/// a file whose imports change a public signature gets a new key and is checked again
+pub fn check_key(hash: u64, imports: &[u64]) -> u64 {
+    let sigs: Vec<String> = imports.iter().map(|s| format!("{:016x}", s)).collect();
+    fnv(format!("{:016x}/{}", key(hash), sigs.join(",")).as_bytes())
+}
+
+///per-file cache of front end results, keyed by `key`
+pub struct Cache {
+    dir: PathBuf,
+}
+
+fn hex(h: u64) -> Json {
+    Json::str(format!("{:016x}", h))
+}
+
+fn unhex(j: &Json) -> Option<u64> {
+    u64::from_str_radix(j.as_str()?, 16).ok()
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn file_for(&self, src: &Path) -> PathBuf {
+        let src = src.canonicalize().unwrap_or_else(|_| src.to_path_buf());
+        self.dir.join(format!("{:016x}.json", fnv(src.to_string_lossy().as_bytes())))
+    }
+
+    ///the entry for `src` if it was written by this compiler version and ast format for the same content
+    pub fn load(&self, src: &Path, hash: u64) -> Option<Entry> {
+        let text = fs::read_to_string(self.file_for(src)).ok()?;
+        let j = Json::parse(&text).ok()?;
+        // entries from before the key existed have none and are rejected here
+        if unhex(j.get("key")?)? != key(hash) {
+            return None;
+        }
+        Some(Entry {
+            hash,
+            ast: nodes_from_json(j.get("ast")?)?,
+            checked: j.get("checked").and_then(unhex),
+        })
+    }
+
+    pub fn store(&self, src: &Path, e: &Entry) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut pairs = vec![
+            ("version", Json::str(env!("CARGO_PKG_VERSION"))),
+            ("format", Json::Num(FORMAT as f64)),
+            ("file", Json::str(src.display().to_string())),
+            ("key", hex(key(e.hash))),
+            ("ast", nodes_to_json(&e.ast)),
+        ];
+        if let Some(k) = e.checked {
+            pairs.push(("checked", hex(k)));
+        }
+        let j = Json::obj(pairs);
+        // write then rename, so a crash never leaves half an entry behind
+        let file = self.file_for(src);
+        let tmp = file.with_extension("tmp");
+        fs::write(&tmp, j.to_string())?;
+        fs::rename(tmp, file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(name: &str) -> (Cache, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("sip-cache-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let src = dir.join("main.sip");
+        fs::write(&src, "fn main() @ i32 { 1 }").unwrap();
+        (Cache::new(dir.join("cache")), src)
+    }
+
+    #[test]
+    fn round_trip() {
+        let (c, src) = cache("round");
+        c.store(&src, &Entry { hash: 1, ast: Vec::new(), checked: None }).unwrap();
+        assert!(c.load(&src, 1).is_some());
+        assert!(c.load(&src, 2).is_none());
+        c.store(&src, &Entry { hash: 1, ast: Vec::new(), checked: Some(5) }).unwrap();
+        assert_eq!(c.load(&src, 1).and_then(|e| e.checked), Some(5));
+    }
+
+    #[test]
+    fn check_key_follows_import_signatures() {
+        let sig = |src: &str| sig_hash(crate::parse(src).get_ast());
+        let a = sig("pub fn f(i32 x) @ i32 { x }\nfn g() @ i32 { 1 }");
+        // bodies and private fns are not part of the signature
+        assert_eq!(a, sig("pub fn f(i32 x) @ i32 { x + 1 }\nfn h() @ i64 { 2 }"));
+        let b = sig("pub fn f(i64 x) @ i32 { 1 }");
+        assert_ne!(a, b);
+        assert_ne!(check_key(1, &[a]), check_key(1, &[b]));
+        assert_ne!(check_key(1, &[a]), check_key(2, &[a]));
+    }
+
+    #[test]
+    fn entries_without_the_key_are_rejected() {
+        let (c, src) = cache("old");
+        fs::create_dir_all(&c.dir).unwrap();
+        // how entries looked before the ast had fn bodies and the key held the format
+        let old = format!(r#"{{"version":"{}","hash":"{:016x}","ast":[]}}"#, env!("CARGO_PKG_VERSION"), 1);
+        fs::write(c.file_for(&src), old).unwrap();
+        assert!(c.load(&src, 1).is_none());
+    }
+}
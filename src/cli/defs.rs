@@ -0,0 +1,138 @@
+use crate::{manifest::Manifest, term::ColorMode, timings::TimingsMode};
+
+///subcommand picked on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cmd {
+    Build,
+    ///`run [path]`, build then call `main` with the interpreter
+    Run,
+    ///`new <name>`, the name is in `Opts::path`
+    New,
+    ///`watch [path]`, rebuild on every change
+    Watch,
+    ///`lsp`, language server over stdio
+    Lsp,
+    ///`fmt [path]`, rewrite sources in canonical style
+    Fmt,
+    ///`help [command]`
+    Help(Option<String>),
+    Version,
+}
+
+///what `build` should produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    ///only check the source and report diagnostics
+    Check,
+    ///the tokens of every file, with line and column
+    Tokens,
+    ///the parsed ast of every file
+    Ast,
+}
+
+impl Emit {
+    pub fn from_arg(s: &str) -> Option<Self> {
+        match s {
+            "check" => Some(Self::Check),
+            "tokens" => Some(Self::Tokens),
+            "ast" => Some(Self::Ast),
+            _ => None,
+        }
+    }
+}
+
+///everything parsed from the command line
+#[derive(Debug, Clone)]
+pub struct Opts {
+    pub cmd: Cmd,
+    ///file/folder given after the command
+    pub path: Option<String>,
+    ///`-o <file>`
+    pub out: Option<String>,
+    pub emit: Emit,
+    ///`--json`: print `--emit` output as JSON
+    pub json: bool,
+    ///`--target`, falls back to the manifest, then `native` (the only one supported yet)
+    pub target: Option<String>,
+    ///`-O<level>`, 0..=3, falls back to the manifest, then 0 (the only one supported yet)
+    pub opt: Option<u8>,
+    ///`run --max-depth <n>`: nested calls allowed before stopping, else the interpreter's default
+    pub max_depth: Option<usize>,
+    pub quiet: bool,
+    ///`--no-cache`: neither read nor write the incremental cache
+    pub no_cache: bool,
+    ///`fmt --check`: report unformatted files instead of rewriting them
+    pub check: bool,
+    ///`--timings[=text|json]`
+    pub timings: TimingsMode,
+    pub color: ColorMode,
+    pub ascii: bool,
+}
+
+impl Default for Opts {
+    fn default() -> Self {
+        Self {
+            cmd: Cmd::Help(None),
+            path: None,
+            out: None,
+            emit: Emit::Check,
+            json: false,
+            target: None,
+            opt: None,
+            max_depth: None,
+            quiet: false,
+            no_cache: false,
+            check: false,
+            timings: TimingsMode::Off,
+            color: ColorMode::Auto,
+            ascii: false,
+        }
+    }
+}
+
+impl Opts {
+    ///take target and optimization level from the manifest unless given on the command line
+    pub fn fill_from(&mut self, m: &Manifest) {
+        if self.target.is_none() {
+            self.target = m.target.clone();
+        }
+        if self.opt.is_none() {
+            self.opt = m.opt;
+        }
+    }
+
+    ///a note for a target or optimization level that is accepted but not supported yet
+    ///
+    /// the command line rejects them, so these only come from the manifest
+    pub fn unsupported(&self) -> Option<String> {
+        if let Some(t) = self.target.as_deref().filter(|&t| t != "native") {
+            return Some(format!("target '{}' is not supported yet, building for native", t));
+        }
+        match self.opt {
+            Some(n) if n > 0 => Some(format!("opt-level {} is not supported yet, building with 0", n)),
+            _ => None,
+        }
+    }
+}
+
+///process exit codes, distinct so build scripts can tell failures apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Ok = 0,
+    ///the source has errors
+    Compile = 1,
+    ///bad command line
+    Usage = 2,
+    ///a file could not be read or written
+    Io = 3,
+    ///the compiler itself failed (panic)
+    Internal = 4,
+    ///`sip run`: the program stopped with a runtime error
+    Runtime = 5,
+}
+
+impl Exit {
+    pub fn exit(self) -> ! {
+        std::process::exit(self as i32)
+    }
+}
@@ -0,0 +1,146 @@
+pub mod defs;
+
+use defs::{Cmd, Emit, Opts};
+use crate::{interp::DEPTH_CAP, term::ColorMode, timings::TimingsMode};
+
+///names accepted as the first positional argument
+pub const COMMANDS: &[&str] = &["build", "run", "new", "watch", "lsp", "fmt", "help", "version"];
+
+///parse the command line (without the binary name)
+///
+/// options may appear before or after the command, `--` ends option parsing
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Opts, String> {
+    let mut opts = Opts::default();
+    let mut positional = Vec::new();
+    let mut wants_help = false;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positional.extend(args.by_ref());
+            break;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            positional.push(arg);
+            continue;
+        }
+        // split `--flag=value`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((f, v)) if f.starts_with("--") || f == "-o" => (f.to_string(), Some(v.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| -> Result<String, String> {
+            match inline.clone().or_else(|| args.next()) {
+                Some(v) => Ok(v),
+                None => Err(format!("missing value for '{}'", name)),
+            }
+        };
+        match flag.as_str() {
+            "-h" | "--help" => wants_help = true,
+            "-V" | "--version" => opts.cmd = Cmd::Version,
+            "-q" | "--quiet" => opts.quiet = true,
+            "--ascii" => opts.ascii = true,
+            "--no-cache" => opts.no_cache = true,
+            "--check" => opts.check = true,
+            "--json" => opts.json = true,
+            // the value is optional, so it is never taken from the next argument
+            "--timings" => {
+                opts.timings = match inline.as_deref() {
+                    None => TimingsMode::Text,
+                    Some(v) => TimingsMode::from_arg(v)
+                        .ok_or_else(|| format!("invalid --timings value '{}' (expected text or json)", v))?,
+                };
+            }
+            "-o" | "--out" => opts.out = Some(value("-o")?),
+            "--target" => {
+                let v = value("--target")?;
+                if v != "native" {
+                    return Err(format!("--target '{}' is not supported yet (only native)", v));
+                }
+                opts.target = Some(v);
+            }
+            "--max-depth" => {
+                let v = value("--max-depth")?;
+                opts.max_depth = Some(v.parse().ok().filter(|&n: &usize| n > 0 && n <= DEPTH_CAP)
+                    .ok_or_else(|| format!("invalid --max-depth value '{}' (expected a number from 1 to {})", v, DEPTH_CAP))?);
+            }
+            "--emit" => {
+                let v = value("--emit")?;
+                opts.emit = Emit::from_arg(&v).ok_or_else(|| format!("invalid --emit value '{}' (expected check, tokens or ast)", v))?;
+            }
+            "--color" => {
+                let v = value("--color")?;
+                opts.color = ColorMode::from_arg(&v)
+                    .ok_or_else(|| format!("invalid --color value '{}' (expected auto, always or never)", v))?;
+            }
+            f if f.starts_with("-O") => {
+                opts.opt = Some(match &f[2..] {
+                    "" => 2,
+                    lvl => match lvl.parse::<u8>() {
+                        Ok(n) if n <= 3 => n,
+                        _ => return Err(format!("invalid optimization level '{}' (expected 0-3)", lvl)),
+                    },
+                });
+                if let Some(n) = opts.opt.filter(|&n| n > 0) {
+                    return Err(format!("-O{} is not supported yet (only -O0)", n));
+                }
+            }
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    if opts.cmd != Cmd::Version {
+        opts.cmd = match positional.next().as_deref() {
+            None => Cmd::Help(None),
+            Some("build") => Cmd::Build,
+            Some("run") => Cmd::Run,
+            Some("new") => Cmd::New,
+            Some("watch") => Cmd::Watch,
+            Some("lsp") => Cmd::Lsp,
+            Some("fmt") => Cmd::Fmt,
+            Some("version") => Cmd::Version,
+            Some("help") => Cmd::Help(positional.next()),
+            Some(other) => return Err(format!("unknown command '{}'", other)),
+        };
+    }
+    if wants_help {
+        opts.cmd = match opts.cmd {
+            Cmd::Build => Cmd::Help(Some("build".into())),
+            Cmd::Run => Cmd::Help(Some("run".into())),
+            Cmd::New => Cmd::Help(Some("new".into())),
+            Cmd::Watch => Cmd::Help(Some("watch".into())),
+            Cmd::Lsp => Cmd::Help(Some("lsp".into())),
+            Cmd::Fmt => Cmd::Help(Some("fmt".into())),
+            Cmd::Version => Cmd::Help(Some("version".into())),
+            other => other,
+        };
+    }
+    opts.path = positional.next();
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+    // only the dumps write anything yet, there is no binary to put at `-o`
+    if opts.out.is_some() && !(opts.cmd == Cmd::Build && opts.emit != Emit::Check) {
+        return Err("-o needs `build --emit=tokens` or `build --emit=ast`, nothing else writes output yet".into());
+    }
+    Ok(opts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Opts, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn out_needs_a_dump() {
+        assert!(parse("build main.sip -o main").is_err());
+        assert!(parse("build main.sip --emit=check -o main").is_err());
+        assert!(parse("run main.sip -o main").is_err());
+        let opts = parse("build main.sip --emit=ast -o main.ast").unwrap();
+        assert_eq!(opts.out.as_deref(), Some("main.ast"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    cache::{CACHE_DIR, Cache},
+    cli::defs::{Exit, Opts},
+    help::help_cmd,
+    manifest::{self, MANIFEST, Manifest, ManifestErr, deps::{self, DepErr}, lock},
+    module::layout::Layout,
+    timings::report,
+};
+
+use super::{Project, load_project, root_of, show_diags, EXT};
+
+///everything a build needs once the command line and manifest are combined
+pub struct Setup {
+    ///command line options, filled in from the manifest
+    pub opts: Opts,
+    ///file or folder to start loading from
+    pub root: PathBuf,
+    pub layout: Layout,
+    pub cache: Option<Cache>,
+    ///directory of the manifest, if the build is a project
+    pub project: Option<PathBuf>,
+}
+
+///find and read the manifest (if any), resolve dependencies and write `sip.lock`
+pub fn setup(opts: &Opts) -> Result<Setup, Exit> {
+    let mut opts = opts.clone();
+    // an explicit file/folder is built as is, unless it is a project directory
+    let project_dir = match &opts.path {
+        Some(p) if Path::new(p).join(MANIFEST).is_file() => Some(PathBuf::from(p)),
+        Some(_) => None,
+        None => match manifest::find(Path::new(".")) {
+            Some(d) => Some(d),
+            None => {
+                eprintln!("Error: Missing file path, and no {} found in the current directory or above.\n", MANIFEST);
+                help_cmd("build");
+                return Err(Exit::Usage);
+            }
+        },
+    };
+    let mut layout = None;
+    let mut project = None;
+    if let Some(dir) = project_dir {
+        let graph = match Manifest::load(&dir).map_err(DepErr::from).and_then(deps::resolve) {
+            Ok(g) => g,
+            Err(e) => {
+                eprintln!("error in manifest!\nerr => {}", e);
+                return Err(if matches!(e, DepErr::Manifest(ManifestErr::Io(..))) { Exit::Io } else { Exit::Compile });
+            }
+        };
+        if let Err(e) = lock::write(&graph) {
+            eprintln!("error writing {}!\nerr => {:?}", lock::LOCKFILE, e);
+            return Err(Exit::Io);
+        }
+        let root = &graph.pkgs[0];
+        opts.fill_from(&root.manifest);
+        if let Some(note) = opts.unsupported() {
+            eprintln!("warning: {} ({})", note, dir.join(MANIFEST).display());
+        }
+        opts.path = Some(root.dir.join(&root.manifest.entry).display().to_string());
+        layout = Some(graph.layout());
+        project = Some(root.dir.clone());
+    }
+    let root = PathBuf::from(opts.path.as_deref().unwrap_or_default());
+    let layout = layout.unwrap_or_else(|| Layout::single(root_of(&root)));
+    // only a project has a `target` directory of its own to keep the cache in
+    let cache = project.as_ref().filter(|_| !opts.no_cache).map(|dir| Cache::new(dir.join(CACHE_DIR)));
+    Ok(Setup { opts, root, layout, cache, project })
+}
+
+///run the front end over the whole build and report timings and diagnostics
+pub fn check(setup: &Setup) -> Exit {
+    match checked(setup) {
+        Ok(_) => Exit::Ok,
+        Err(code) => code,
+    }
+}
+
+///the project of a build, once timings and diagnostics are reported, `Err` if it has errors
+pub fn checked(setup: &Setup) -> Result<Project, Exit> {
+    let root = &setup.root;
+    let project = match load_project(root, setup.layout.clone(), setup.cache.as_ref()) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("error reading '{}'!\nerr => {:?}", root.display(), e);
+            return Err(Exit::Io);
+        }
+    };
+    if project.units.is_empty() && project.fails.is_empty() {
+        eprintln!("Error: no .{} files found in '{}'.", EXT, root.display());
+        return Err(Exit::Usage);
+    }
+
+    let mut code = Exit::Ok;
+    for f in &project.fails {
+        eprintln!("error reading file '{}'!\nerr => {:?}", f.path.display(), f.err);
+        code = Exit::Io;
+    }
+
+    let times: Vec<_> = project.units.iter().map(|u| u.times.clone()).collect();
+    report(&times, setup.opts.timings);
+    show_diags(&project.units);
+    if code == Exit::Ok && project.units.iter().any(|u| u.parser.erroccur()) {
+        code = Exit::Compile;
+    }
+    if code == Exit::Ok { Ok(project) } else { Err(code) }
+}
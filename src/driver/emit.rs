@@ -0,0 +1,103 @@
+use std::fs;
+
+use crate::{
+    ast::{dump::tree, json::nodes_to_json},
+    cli::defs::{Emit, Exit},
+    json::Json,
+    token::{defs::Token, lexer::lex},
+};
+
+use super::{Unit, build::Setup, load_project, show_diags, EXT};
+
+///1-based column of the byte offset `off`, counted in characters
+pub fn column(src: &[u8], off: usize) -> usize {
+    let off = off.min(src.len());
+    let line_start = src[..off].iter().rposition(|&b| b == b'\n').map_or(0, |p| p + 1);
+    String::from_utf8_lossy(&src[line_start..off]).chars().count() + 1
+}
+
+fn token_json(src: &[u8], t: &Token) -> Json {
+    Json::obj([
+        ("kind", Json::str(format!("{:?}", t.TT))),
+        ("value", t.value.clone().map_or(Json::Null, Json::Str)),
+        ("line", Json::Num(t.line as f64)),
+        ("column", Json::Num(column(src, t.start) as f64)),
+        ("start", Json::Num(t.start as f64)),
+        ("end", Json::Num(t.end as f64)),
+    ])
+}
+
+fn text(kind: Emit, u: &Unit) -> String {
+    let mut out = format!("{}\n", u.path.display());
+    match kind {
+        Emit::Tokens => {
+            for t in lex(u.text()) {
+                let pos = format!("{}:{}", t.line, column(&u.src, t.start));
+                out.push_str(&format!("  {:<8} {:<12} {}\n", pos, format!("{:?}", t.TT), t.text()));
+            }
+        }
+        _ => out.push_str(&tree(u.parser.get_ast())),
+    }
+    out
+}
+
+fn json(kind: Emit, units: &[Unit]) -> Json {
+    let key = if kind == Emit::Tokens { "tokens" } else { "ast" };
+    Json::obj([
+        ("version", Json::str(env!("CARGO_PKG_VERSION"))),
+        ("emit", Json::str(key)),
+        ("files", Json::Arr(units.iter().map(|u| Json::obj([
+            ("file", Json::str(u.path.display().to_string())),
+            (key, match kind {
+                Emit::Tokens => Json::Arr(lex(u.text()).iter().map(|t| token_json(&u.src, t)).collect()),
+                _ => nodes_to_json(u.parser.get_ast()),
+            }),
+        ])).collect())),
+    ])
+}
+
+///`--emit=tokens|ast`: dump the front end's view of every file, to `-o` or stdout
+///
+/// the cache is bypassed so token spans always match the files on disk
+pub fn emit(setup: &Setup) -> Exit {
+    let opts = &setup.opts;
+    let root = &setup.root;
+    let project = match load_project(root, setup.layout.clone(), None) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("error reading '{}'!\nerr => {:?}", root.display(), e);
+            return Exit::Io;
+        }
+    };
+    if project.units.is_empty() && project.fails.is_empty() {
+        eprintln!("Error: no .{} files found in '{}'.", EXT, root.display());
+        return Exit::Usage;
+    }
+
+    let mut code = Exit::Ok;
+    for f in &project.fails {
+        eprintln!("error reading file '{}'!\nerr => {:?}", f.path.display(), f.err);
+        code = Exit::Io;
+    }
+
+    let out = if opts.json {
+        format!("{}\n", json(opts.emit, &project.units))
+    } else {
+        project.units.iter().map(|u| text(opts.emit, u)).collect::<Vec<_>>().join("\n")
+    };
+    match &opts.out {
+        Some(path) => {
+            if let Err(e) = fs::write(path, out) {
+                eprintln!("error writing file '{}'!\nerr => {:?}", path, e);
+                return Exit::Io;
+            }
+        }
+        None => print!("{}", out),
+    }
+
+    show_diags(&project.units);
+    if code == Exit::Ok && project.units.iter().any(|u| u.parser.erroccur()) {
+        code = Exit::Compile;
+    }
+    code
+}
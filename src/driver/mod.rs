@@ -0,0 +1,276 @@
+pub mod build;
+pub mod emit;
+pub mod run;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io, mem,
+    path::{Path, PathBuf},
+};
+
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+use crate::{
+    ast::defs::NodeT,
+    cache::{Cache, Entry, check_key, fnv, sig_hash},
+    err::def::PErr,
+    module::{graph::ModGraph, layout::Layout, resolve::Resolver},
+    parser::Parser,
+    sema::{self, Bindings},
+    timings::{FileTimings, Phase},
+    token::lexer::lex,
+};
+
+///extension of sip source files
+pub const EXT: &str = "sip";
+
+///a single source file after the front end ran on it
+pub struct Unit {
+    pub path: PathBuf,
+    pub src: Mmap,
+    pub parser: Parser,
+    pub times: FileTimings,
+    ///content hash of `src`
+    pub hash: u64,
+    ///the ast came from the cache
+    pub cached: bool,
+    ///`check_key` the cache entry last type checked clean under, only for a cached ast
+    pub checked: Option<u64>,
+    ///what every name in the ast refers to, filled in by `load_project`
+    pub binds: Bindings,
+}
+
+///a file that could not be read
+#[derive(Debug)]
+pub struct IoFail {
+    pub path: PathBuf,
+    pub err: io::Error,
+}
+
+///every `*.sip` file under `root` (or `root` itself if it is a file), sorted by path
+///
+/// hidden directories are skipped
+pub fn discover(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    if root.is_dir() {
+        walk(root, &mut out)?;
+        out.sort();
+    } else {
+        fs::metadata(root)?;
+        out.push(root.to_path_buf());
+    }
+    Ok(out)
+}
+
+fn walk(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.'));
+        if path.is_dir() {
+            if !hidden {
+                walk(&path, out)?;
+            }
+        } else if path.extension().is_some_and(|e| e == EXT) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+///read a single file, then take its ast from the cache or lex and parse it
+pub fn load(path: &Path, cache: Option<&Cache>) -> Result<Unit, IoFail> {
+    let mut times = FileTimings::new(path.display().to_string());
+    let (src, hash) = times.time(Phase::Read, || -> io::Result<(Mmap, u64)> {
+        let file = File::open(path)?;
+        let src = unsafe { Mmap::map(&file) }?;
+        let hash = fnv(&src[..]);
+        Ok((src, hash))
+    }).map_err(|err| IoFail { path: path.to_path_buf(), err })?;
+
+    if let Some(entry) = cache.and_then(|c| times.time(Phase::Cache, || c.load(path, hash))) {
+        let parser = Parser::with_ast(entry.ast);
+        return Ok(Unit { path: path.to_path_buf(), src, parser, times, hash, cached: true, checked: entry.checked, binds: Bindings::default() });
+    }
+    let mut unit = Unit {
+        path: path.to_path_buf(), src, parser: Parser::new(Vec::new()), times, hash, cached: false, checked: None, binds: Bindings::default(),
+    };
+    unit.parse();
+    Ok(unit)
+}
+
+impl Unit {
+    ///the source as text, empty when it is not UTF-8 since the parser already reported that
+    pub fn text(&self) -> &str {
+        std::str::from_utf8(&self.src[..]).unwrap_or_default()
+    }
+
+    ///lex and parse `src`
+    fn parse(&mut self) {
+        let text = match std::str::from_utf8(&self.src[..]) {
+            Ok(t) => t,
+            Err(e) => {
+                self.parser = Parser::not_utf8(&self.src[..], e);
+                return;
+            }
+        };
+        let tokens = self.times.time(Phase::Lex, || lex(text));
+        self.parser = Parser::new(tokens);
+        self.times.time(Phase::Parse, || self.parser.parse());
+    }
+}
+
+///run the front end on every file in parallel, results keep the order of `paths`
+pub fn load_all(paths: &[PathBuf], cache: Option<&Cache>) -> Vec<Result<Unit, IoFail>> {
+    paths.par_iter().map(|p| load(p, cache)).collect()
+}
+
+///every unit of a build plus the module graph tying them together
+pub struct Project {
+    ///where module paths point, per package
+    pub layout: Layout,
+    ///sorted by path, same order as `graph.names`
+    pub units: Vec<Unit>,
+    pub fails: Vec<IoFail>,
+    pub graph: ModGraph,
+    pub resolver: Resolver,
+}
+
+///directory module paths of a plain `sip build <path>` are relative to
+pub fn root_of(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.to_path_buf()
+    } else {
+        path.parent().map(Path::to_path_buf).unwrap_or_default()
+    }
+}
+
+///load `path` (a file or a folder) plus every module it imports, then resolve names
+///
+/// with a cache, unchanged files skip lexing and parsing, and every file that ends
+/// up without errors is written back. names are always resolved again, but a file
+/// that type checked without diagnostics skips the type check until it or the
+/// public signature of a module it imports changes.
+pub fn load_project(path: &Path, layout: Layout, cache: Option<&Cache>) -> io::Result<Project> {
+    let mut units = Vec::new();
+    let mut fails = Vec::new();
+    let mut wave = discover(path)?;
+    let mut seen: HashSet<PathBuf> = wave.iter().cloned().collect();
+    // pull in imported files that were not part of the initial set, one wave at a time
+    while !wave.is_empty() {
+        let start = units.len();
+        for res in load_all(&wave, cache) {
+            match res {
+                Ok(u) => units.push(u),
+                Err(f) => fails.push(f),
+            }
+        }
+        wave = units[start..].iter()
+            .flat_map(|u| u.parser.get_ast().iter().map(move |n| (u, n)))
+            .filter_map(|(u, n)| match n {
+                NodeT::ImportNode(imp) => Some(layout.import(&u.path, &imp.path).1),
+                _ => None,
+            })
+            .filter(|p| p.is_file() && seen.insert(p.clone()))
+            .collect();
+    }
+    units.sort_by_key(|u| layout.mod_path(&u.path));
+
+    let graph = ModGraph::build(&layout, &mut units);
+    let resolver = Resolver::new(&graph, &mut units);
+    for (m, u) in units.iter_mut().enumerate() {
+        let (binds, errs) = u.times.time(Phase::Resolve, || sema::resolve(&resolver, m, u.parser.get_ast(), &[]));
+        u.binds = binds;
+        if !errs.is_empty() {
+            u.parser.errs.extend(errs);
+            u.parser.errs.sort_by_key(|e| (e.line, e.start));
+        }
+    }
+    let keys = check_keys(&layout, &units);
+    // the checker reads every ast, so the timings are moved out to be written in parallel
+    let mut times: Vec<FileTimings> = units.iter_mut().map(|u| mem::take(&mut u.times)).collect();
+    let checked: Vec<Option<Vec<PErr>>> = {
+        let asts: Vec<&[NodeT]> = units.iter().map(|u| u.parser.get_ast().as_slice()).collect();
+        units.par_iter().zip(times.par_iter_mut()).zip(&keys).enumerate().map(|(m, ((u, times), &key))| {
+            if u.checked == Some(key) && u.parser.errs.is_empty() {
+                return None;
+            }
+            Some(times.time(Phase::Typecheck, || sema::check(&asts, m, &u.binds, &[])))
+        }).collect()
+    };
+    for ((u, times), errs) in units.iter_mut().zip(times).zip(checked) {
+        u.times = times;
+        let Some(errs) = errs else { continue };
+        if !errs.is_empty() {
+            u.parser.errs.extend(errs);
+            u.parser.errs.sort_by_key(|e| (e.line, e.start));
+        }
+    }
+
+    if let Some(cache) = cache {
+        for (u, key) in units.iter().zip(keys) {
+            let checked = u.parser.errs.is_empty().then_some(key);
+            if !u.parser.erroccur() && (!u.cached || u.checked != checked) {
+                let entry = Entry { hash: u.hash, ast: u.parser.get_ast().clone(), checked };
+                // a cache that cannot be written only costs time on the next build
+                let _ = cache.store(&u.path, &entry);
+            }
+        }
+    }
+    Ok(Project { layout, units, fails, graph, resolver })
+}
+
+///`check_key` per unit, from the `sig_hash` of the modules it imports
+fn check_keys(layout: &Layout, units: &[Unit]) -> Vec<u64> {
+    let sigs: HashMap<String, u64> = units.iter().map(|u| (layout.mod_path(&u.path), sig_hash(u.parser.get_ast()))).collect();
+    units.iter().map(|u| {
+        let imports: Vec<u64> = u.parser.get_ast().iter().filter_map(|n| match n {
+            // a missing module is an error, so its key never gets stored
+            NodeT::ImportNode(imp) => Some(sigs.get(&layout.import(&u.path, &imp.path).0).copied().unwrap_or(0)),
+            _ => None,
+        }).collect();
+        check_key(u.hash, &imports)
+    }).collect()
+}
+
+///print the diagnostics of every unit, sorted by file and line
+pub fn show_diags(units: &[Unit]) {
+    let mut sorted: Vec<&Unit> = units.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path));
+    for u in sorted {
+        u.parser.show_errs(&u.path.display().to_string(), &u.src);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///whether each unit of a build of `dir` ran the type check, in path order
+    fn typechecked(dir: &Path, cache: &Cache) -> Vec<bool> {
+        let p = load_project(dir, Layout::single(dir.to_path_buf()), Some(cache)).unwrap();
+        p.units.iter().map(|u| u.times.phases.iter().any(|(ph, _)| *ph == Phase::Typecheck)).collect()
+    }
+
+    #[test]
+    fn signature_change_rechecks_dependents() {
+        let dir = std::env::temp_dir().join(format!("sip-driver-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.sip"), "import util;\nfn main() @ i32 { util.add(1, 2) }\n").unwrap();
+        fs::write(dir.join("util.sip"), "pub fn add(i32 a, i32 b) @ i32 { a + b }\n").unwrap();
+        let cache = Cache::new(dir.join("cache"));
+
+        assert_eq!(typechecked(&dir, &cache), [true, true]);
+        assert_eq!(typechecked(&dir, &cache), [false, false]);
+        // a body change keeps the signature, only the file itself is checked again
+        fs::write(dir.join("util.sip"), "pub fn add(i32 a, i32 b) @ i32 { b + a }\n").unwrap();
+        assert_eq!(typechecked(&dir, &cache), [false, true]);
+        fs::write(dir.join("util.sip"), "pub fn add(i32 a) @ i32 { a }\n").unwrap();
+        assert_eq!(typechecked(&dir, &cache), [true, true]);
+        let p = load_project(&dir, Layout::single(dir.clone()), Some(&cache)).unwrap();
+        assert!(p.units[0].parser.erroccur());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,55 @@
+use std::{panic, thread};
+
+use crate::{
+    ast::defs::VarT,
+    cli::defs::Exit,
+    interp::{Engine, RunErr},
+};
+
+use super::{EXT, build::{Setup, checked}};
+
+///`sip run`: check the build, then call its `main` with the interpreter and print the result
+///
+/// for a folder, `main` is taken from `main.sip` at its root
+pub fn run(setup: &Setup) -> Exit {
+    let project = match checked(setup) {
+        Ok(p) => p,
+        Err(code) => return code,
+    };
+    let entry = if setup.root.is_dir() { setup.root.join(format!("main.{}", EXT)) } else { setup.root.clone() };
+    let mut engine = Engine::from_project(&project, &entry);
+    if engine.func("main").is_none() {
+        eprintln!("Error: no `main` function in '{}'.", entry.display());
+        return Exit::Compile;
+    }
+    if let Some(depth) = setup.opts.max_depth {
+        engine.set_max_depth(depth);
+    }
+    // the one call gets a thread with the stack the depth limit needs, see `Engine::call`
+    let result = thread::scope(|s| {
+        thread::Builder::new()
+            .stack_size(engine.stack_size())
+            .spawn_scoped(s, || engine.call("main", &[]))
+            .map(|h| h.join().unwrap_or_else(|p| panic::resume_unwind(p)))
+    });
+    match result {
+        Ok(Ok(v)) => {
+            // a `main` returning nothing prints nothing
+            if !matches!(v, VarT::Nil) {
+                println!("{}", v);
+            }
+            Exit::Ok
+        }
+        Ok(Err(e)) => {
+            eprintln!("runtime error: {}", e);
+            if let RunErr::Depth { .. } = e {
+                eprintln!("help: recursion that deep is usually missing a base case, else raise the limit with --max-depth <n>");
+            }
+            Exit::Runtime
+        }
+        Err(e) => {
+            eprintln!("Error: cannot start the interpreter with a stack for {} nested calls: {}", engine.max_depth(), e);
+            Exit::Internal
+        }
+    }
+}
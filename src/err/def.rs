@@ -16,6 +16,37 @@ pub enum ErrT{
     RetTNF,
     ///Var type invalid
     InvVT,
+    ///identifier that is neither a keyword, a type nor a known name
+    UnknownIdent,
+    ///`import` of a module with no matching file
+    UnknownModule,
+    ///modules importing each other in a loop
+    ImportCycle,
+    ///a name no param, fn or host fn declares
+    UndefName,
+    ///two params of one fn with the same name
+    DupParam,
+    ///two fns, consts or globals of one module with the same name
+    DupFunc,
+    ///a value of one type where another is needed
+    TypeMismatch,
+    ///a call with the wrong number of arguments
+    ArgCount,
+    ///a statement no path reaches, only a warning
+    Unreachable,
+    ///`break` or `continue` outside of a loop
+    OutsideLoop,
+    ///assignment to something that cannot change
+    Immutable,
+    ///a `const` or `global` value that cannot be computed while compiling
+    ConstEval,
+}
+
+impl ErrT {
+    ///warnings are shown but do not fail the build
+    pub fn is_warning(&self) -> bool {
+        matches!(self, ErrT::Unreachable)
+    }
 }
 #[derive(Debug)]
 pub struct PErr{
@@ -23,4 +54,6 @@ pub struct PErr{
     pub line: usize,
     pub start: usize,
     pub end: usize,
+    ///extra help line, e.g. a "did you mean" suggestion
+    pub help: Option<String>,
 }
@@ -1,27 +1,10 @@
 use crate::err::def::{ErrT, PErr};
-use memmap2::Mmap;
 use colored::*;
+use crate::term::glyphs;
 
-pub fn diserr(e: &PErr, mmap: &Mmap) -> String {
-    let full_src = std::str::from_utf8(&mmap[..]).unwrap_or("<invalid utf8>");
-    let line = e.line;
-    let src_line = {
-        match e.et {
-            ErrT::RetTNF | ErrT::RetTMisMatch(_) => {
-                full_src.lines().nth(line.saturating_sub(2)).unwrap_or("<unknown line>")
-            },
-            _ => {
-                full_src.lines().nth(line.saturating_sub(1)).unwrap_or("<unknown line>")
-            }
-        }
-    };
-    let full_line_len = src_line.chars().count();
-    let full_underline = "─".repeat(full_line_len);
-    let indicator_len = if e.end > e.start { e.end - e.start } else { 1 };
-    let indicator = format!("{}{}", " ".repeat(e.start), "^".repeat(indicator_len));
-
-    // Error type descriptions with bold colors
-    let err_desc = match &e.et {
+///short description and default help line of an error kind
+pub fn describe(et: &ErrT) -> (&'static str, &'static str) {
+    let err_desc = match et {
         ErrT::FuncNameNotFound   => "Function name not found",
         ErrT::SyntaxErr(desc)    => desc,
         ErrT::ExpectParamName    => "Expected parameter name",
@@ -29,35 +12,83 @@ pub fn diserr(e: &PErr, mmap: &Mmap) -> String {
         ErrT::RetTMisMatch(desc) => desc,
         ErrT::RetTNF             => "Return value not found",
         ErrT::InvVT              => "Invalid variable type",
+        ErrT::UnknownIdent       => "Unknown identifier",
+        ErrT::UnknownModule      => "Unknown module",
+        ErrT::ImportCycle        => "Import cycle",
+        ErrT::UndefName          => "Undefined name",
+        ErrT::DupParam           => "Duplicate parameter",
+        ErrT::DupFunc            => "Duplicate definition",
+        ErrT::TypeMismatch       => "Mismatched types",
+        ErrT::ArgCount           => "Wrong number of arguments",
+        ErrT::Unreachable        => "Unreachable code",
+        ErrT::OutsideLoop        => "Jump outside of a loop",
+        ErrT::Immutable          => "Cannot assign to an immutable value",
+        ErrT::ConstEval          => "Cannot evaluate at compile time",
     };
 
-    // Help suggestions based on the error type
-    let help_msg = match &e.et {
+    let help_msg = match et {
         ErrT::FuncNameNotFound   => "Provide a valid function name.",
         ErrT::SyntaxErr(_)       => "",
         ErrT::ExpectParamName    => "Each parameter must have an identifier.",
-        ErrT::ExpectedAT         => "Ensure exactly one '@' appears after the parameter list.",
+        ErrT::ExpectedAT         => "Write '@' and the return type after the parameter list, or leave both out to return nothing.",
         ErrT::RetTMisMatch(_)    => "Verify that the return type and value match.",
         ErrT::RetTNF             => "Insert a return statement with an appropriate value.",
         ErrT::InvVT              => "Check the declared type for correctness.",
+        ErrT::UnknownIdent       => "Check the spelling of this name.",
+        ErrT::UnknownModule      => "Module paths follow file paths: `import a.b;` loads `a/b.sip` from the project root.",
+        ErrT::ImportCycle        => "Move the shared functions into a module both can import.",
+        ErrT::UndefName          => "Declare it as a parameter, define the function, or import the module that has it.",
+        ErrT::DupParam           => "Give each parameter its own name.",
+        ErrT::DupFunc            => "Rename or remove one of the definitions.",
+        ErrT::TypeMismatch       => "Numeric types never mix implicitly, convert with `as`.",
+        ErrT::ArgCount           => "Pass exactly one argument per parameter.",
+        ErrT::Unreachable        => "Remove it, or move it before the `ret`, `break` or `continue`.",
+        ErrT::OutsideLoop        => "Use `ret` to leave a function.",
+        ErrT::Immutable          => "Only `let mut` locals and globals can change after they are declared.",
+        ErrT::ConstEval          => "Consts and globals are set from literals, operators, casts and other consts.",
     };
+    (err_desc, help_msg)
+}
+
+///render `e` as a boxed diagnostic, `src` is the text of `file`
+pub fn diserr(e: &PErr, file: &str, src: &[u8]) -> String {
+    // only a source that is not UTF-8 has replaced bytes, and its one error sits before the first of them
+    let full_src = &*String::from_utf8_lossy(src);
+    let line = e.line;
+    let src_line = full_src.lines().nth(line.saturating_sub(1)).unwrap_or("<unknown line>");
+    let full_line_len = src_line.chars().count();
+    let g = glyphs();
+    let full_underline = g.line.repeat(full_line_len);
+    let indicator_len = if e.end > e.start { e.end - e.start } else { 1 };
+    // `start` is a byte offset into the file, turn it into a column on its line
+    let col = full_src.get(..e.start.min(full_src.len()))
+        .map(|before| before.len() - before.rfind('\n').map_or(0, |i| i + 1))
+        .unwrap_or(e.start);
+    let indicator = format!("{}{}", " ".repeat(col), "^".repeat(indicator_len));
+
+    let (err_desc, help_msg) = describe(&e.et);
+    let help_msg = e.help.as_deref().unwrap_or(help_msg);
 
     // Structure for visual formatting with bold colors
-    let header = format!("╭── [ERROR] {}", err_desc.red().bold());
-    let location = format!("│   ├─ [LOCATION] Line: {} | Column: {}", line, e.start).yellow().bold();
-    let source_header = "│   ├─ [SOURCE]".to_string().cyan().bold();
-    let source_line = format!("│   │   {}", src_line).white().bold();
-    let underline_line = format!("│   │   {}", full_underline.red().bold());
-    let indicator_line = format!("│   │   {}", indicator.red().bold());
-    let explanation = format!("│   ├─ [EXPLANATION] {}", err_desc.magenta().bold());
+    let header = if e.et.is_warning() {
+        format!("{}{} [WARNING] {}", g.top, g.line.repeat(2), err_desc.yellow().bold())
+    } else {
+        format!("{}{} [ERROR] {}", g.top, g.line.repeat(2), err_desc.red().bold())
+    };
+    let location = format!("{}   {} [LOCATION] {} | Line: {} | Column: {}", g.bar, g.tee, file, line, col + 1).yellow().bold();
+    let source_header = format!("{}   {} [SOURCE]", g.bar, g.tee).cyan().bold();
+    let source_line = format!("{}   {}   {}", g.bar, g.bar, src_line).white().bold();
+    let underline_line = format!("{}   {}   {}", g.bar, g.bar, full_underline.red().bold());
+    let indicator_line = format!("{}   {}   {}", g.bar, g.bar, indicator.red().bold());
+    let explanation = format!("{}   {} [EXPLANATION] {}", g.bar, g.tee, err_desc.magenta().bold());
     let help_line = if !help_msg.is_empty() {
-        format!("│   └─ [HELP] {}", help_msg.green().italic().bold())
+        format!("{}   {} [HELP] {}", g.bar, g.end, help_msg.green().italic().bold())
     } else {
-        "│   └─ [HELP] Unable to give you help for this".to_string().dimmed().to_string()
+        format!("{}   {} [HELP] Unable to give you help for this", g.bar, g.end).dimmed().to_string()
     };
 
     // Closing border with rounded corners, bold
-    let footer = "╰────────────────────────────────────────────".green().bold();
+    let footer = format!("{}{}", g.bot, g.line.repeat(44)).green().bold();
 
     // Final output assembly
     format!(
@@ -1,2 +1,3 @@
 pub mod def;
-pub mod edis;
\ No newline at end of file
+pub mod edis;
+pub mod suggest;
@@ -0,0 +1,120 @@
+///words people coming from other languages tend to type, mapped to the sip spelling
+const ALIASES: &[(&str, &str)] = &[
+    ("return", "ret"),
+    ("fun", "fn"),
+    ("func", "fn"),
+    ("function", "fn"),
+    ("def", "fn"),
+    ("int", "i32"),
+    ("long", "i64"),
+    ("byte", "u8"),
+    ("float", "f32"),
+    ("double", "f64"),
+    ("null", "nil"),
+    ("none", "nil"),
+];
+
+///optimal string alignment distance (levenshtein + adjacent transpositions)
+pub fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let w = b.len() + 1;
+    let mut d = vec![0usize; (a.len() + 1) * w];
+    for i in 0..=a.len() {
+        d[i * w] = i;
+    }
+    for (j, cell) in d.iter_mut().enumerate().take(w) {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut v = (d[(i - 1) * w + j] + 1)
+                .min(d[i * w + j - 1] + 1)
+                .min(d[(i - 1) * w + j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                v = v.min(d[(i - 2) * w + j - 2] + 1);
+            }
+            d[i * w + j] = v;
+        }
+    }
+    d[a.len() * w + b.len()]
+}
+
+///closest candidate to `word`, if any is close enough to be a plausible typo
+///
+/// aliases from other languages are matched too, so `retrun` gives `ret`.
+/// ties go to the alphabetically first name, whatever order `candidates` is in
+pub fn suggest<'a, I>(word: &str, candidates: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let limit = (word.chars().count() / 3).max(1);
+    let mut best: Option<(usize, &str)> = None;
+    let mut consider = |cand: &str, target: &'a str| {
+        let dist = distance(word, cand);
+        if dist <= limit && best.is_none_or(|b| (dist, target) < b) {
+            best = Some((dist, target));
+        }
+    };
+    let candidates: Vec<&'a str> = candidates.into_iter().collect();
+    for &cand in &candidates {
+        if cand != word {
+            consider(cand, cand);
+        }
+    }
+    for &(alias, target) in ALIASES {
+        if candidates.contains(&target) {
+            consider(alias, target);
+        }
+    }
+    best.map(|(_, s)| s.to_string())
+}
+
+///the help line shown under a diagnostic for a suggestion
+pub fn did_you_mean(s: &str) -> String {
+    format!("did you mean `{}`?", s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance("count", "count"), 0);
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("cont", "count"), 1);
+        assert_eq!(distance("conut", "count"), 1);
+        assert_eq!(distance("cuont", "count"), 1);
+        assert_eq!(distance("cnt", "count"), 2);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("é", "e"), 1);
+    }
+
+    #[test]
+    fn cutoff_grows_with_the_word() {
+        // one edit for words under six chars, two from six
+        assert_eq!(suggest("ab", ["ac"]), Some("ac".into()));
+        assert_eq!(suggest("ab", ["cd"]), None);
+        assert_eq!(suggest("cnt", ["count"]), None);
+        assert_eq!(suggest("lenght", ["length"]), Some("length".into()));
+        assert_eq!(suggest("lngth", ["length"]), Some("length".into()));
+        assert_eq!(suggest("lnth", ["length"]), None);
+    }
+
+    #[test]
+    fn exact_and_closest() {
+        // the word itself is not a suggestion
+        assert_eq!(suggest("count", ["count"]), None);
+        assert_eq!(suggest("counter", ["count", "counts", "counter2"]), Some("counter2".into()));
+        assert_eq!(suggest("retrun", ["ret", "fn"]), Some("ret".into()));
+        assert_eq!(suggest("int", ["i32", "i64"]), Some("i32".into()));
+    }
+
+    #[test]
+    fn ties_do_not_depend_on_order() {
+        assert_eq!(suggest("bat", ["cat", "bar", "hat"]), Some("bar".into()));
+        assert_eq!(suggest("bat", ["hat", "cat", "bar"]), Some("bar".into()));
+    }
+}
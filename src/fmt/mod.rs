@@ -0,0 +1,102 @@
+pub mod style;
+
+use std::{fs, path::{Path, PathBuf}};
+
+use crate::{
+    cli::defs::{Exit, Opts},
+    driver::discover,
+    help::help_cmd,
+    manifest::{self, MANIFEST},
+};
+
+///`sip fmt`: rewrite every file in canonical style, or with `--check` only list the ones that are not
+pub fn fmt(opts: &Opts) -> Exit {
+    let root = match &opts.path {
+        Some(p) => PathBuf::from(p),
+        None => match manifest::find(Path::new(".")) {
+            Some(d) => d,
+            None => {
+                eprintln!("Error: Missing file path, and no {} found in the current directory or above.\n", MANIFEST);
+                help_cmd("fmt");
+                return Exit::Usage;
+            }
+        },
+    };
+    let files = match discover(&root) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("error reading '{}'!\nerr => {:?}", root.display(), e);
+            return Exit::Io;
+        }
+    };
+
+    let mut code = Exit::Ok;
+    for path in &files {
+        let src = match fs::read(path) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("error reading file '{}'!\nerr => {:?}", path.display(), e);
+                code = Exit::Io;
+                continue;
+            }
+        };
+        let out = match style::format(&src) {
+            Ok(o) => o,
+            Err(e) => {
+                eprintln!("error formatting '{}'!\nerr => line {}: {}", path.display(), e.line, e.msg);
+                if code == Exit::Ok {
+                    code = Exit::Compile;
+                }
+                continue;
+            }
+        };
+        if out.as_bytes() == src {
+            continue;
+        }
+        if opts.check {
+            println!("{}", path.display());
+            if code == Exit::Ok {
+                code = Exit::Compile;
+            }
+        } else if let Err(e) = fs::write(path, out) {
+            eprintln!("error writing file '{}'!\nerr => {:?}", path.display(), e);
+            code = Exit::Io;
+        } else if !opts.quiet {
+            println!("formatted {}", path.display());
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::style::format;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/fmt.sip");
+
+    fn fmt(src: &str) -> String {
+        format(src.as_bytes()).unwrap_or_else(|e| panic!("line {}: {}", e.line, e.msg))
+    }
+
+    #[test]
+    fn idempotent() {
+        let once = fmt(FIXTURE);
+        assert_ne!(once, FIXTURE, "the fixture should need formatting");
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn comments_survive() {
+        let out = fmt(FIXTURE);
+        for comment in FIXTURE.lines().filter_map(|l| l.find("//").map(|i| &l[i..])) {
+            assert!(out.contains(comment), "lost `{}`", comment);
+        }
+    }
+
+    #[test]
+    fn same_tokens() {
+        let out = fmt(FIXTURE);
+        let texts = |src: &str| crate::lex(src).into_iter().map(|t| (t.TT, t.value)).collect::<Vec<_>>();
+        assert_eq!(texts(&out), texts(FIXTURE));
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+
+use crate::token::{defs::{IDType, TT, Token}, lexer::lex_trivia};
+
+///one level of body indentation
+pub const INDENT: &str = "    ";
+///bodies are only kept on one line if the line fits in this many columns
+pub const MAX_WIDTH: usize = 80;
+
+///why a file could not be formatted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FmtErr {
+    pub line: usize,
+    pub msg: String,
+}
+
+///whether `t` is separated from `prev` on the same line
+fn needs_space(prev: Option<&Token>, t: &Token) -> bool {
+    let Some(p) = prev else { return false };
+    match (&p.TT, &t.TT) {
+        (_, TT::Comma | TT::SemiColon | TT::Colon | TT::RsmallB | TT::RBigB | TT::Dot) => false,
+        (TT::LSmallB | TT::LBigB | TT::Dot | TT::Bang, _) => false,
+        (TT::DotDot, _) | (_, TT::DotDot) => false,
+        // `name(` of a declaration or call
+        (TT::Ident(IDType::Nil), TT::LSmallB) => false,
+        // `_` only exists inside names for now, keep it the way it was written
+        (TT::Underscore, _) | (_, TT::Underscore) => p.end != t.start,
+        _ => true,
+    }
+}
+
+///number of line breaks in `src[from..to]`
+fn breaks(src: &[u8], from: usize, to: usize) -> usize {
+    src.get(from..to).map_or(0, |s| s.iter().filter(|&&b| b == b'\n').count())
+}
+
+struct Writer<'a> {
+    src: &'a [u8],
+    toks: &'a [Token],
+    out: String,
+    ///current line, without indentation
+    line: String,
+    ///last token written to `line`
+    last: Option<&'a Token>,
+    depth: usize,
+    ///starts of the `-` tokens that negate instead of subtract
+    unary: HashSet<usize>,
+}
+
+///whether a `-` after `prev` negates, i.e. `prev` cannot end an operand
+fn starts_operand(prev: Option<&Token>) -> bool {
+    !prev.is_some_and(|p| matches!(p.TT, TT::NUM | TT::Ident(_) | TT::True | TT::False | TT::RsmallB | TT::RBigB))
+}
+
+impl<'a> Writer<'a> {
+    fn space(&self, prev: Option<&Token>, t: &Token) -> bool {
+        if prev.is_some_and(|p| p.TT == TT::Minus && self.unary.contains(&p.start)) {
+            return false;
+        }
+        needs_space(prev, t)
+    }
+
+    fn push(&mut self, t: &'a Token) {
+        if self.space(self.last, t) {
+            self.line.push(' ');
+        }
+        self.line.push_str(t.text());
+        self.last = Some(t);
+    }
+
+    ///whether the `}` at `close` shares its line with an `else`
+    fn joins(&self, close: usize) -> bool {
+        self.toks.get(close + 1).is_some_and(|n| n.TT == TT::Else)
+    }
+
+    fn flush(&mut self) {
+        if !self.line.is_empty() {
+            self.out.push_str(&INDENT.repeat(self.depth));
+            self.out.push_str(&self.line);
+            self.out.push('\n');
+            self.line.clear();
+        }
+        self.last = None;
+    }
+
+    ///an empty line, never at the start of the file or of a block and never two in a row
+    fn blank(&mut self) {
+        self.flush();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") && !self.out.ends_with("{\n") {
+            self.out.push('\n');
+        }
+    }
+
+    ///`{ stmt }` if the block at `open` is short and holds a single line without comments
+    fn one_line(&self, open: usize) -> Option<(String, usize)> {
+        let mut body = String::new();
+        let mut prev = None;
+        for (i, t) in self.toks.iter().enumerate().skip(open + 1) {
+            match t.TT {
+                TT::RCurlyB => {
+                    let body = if body.is_empty() { "{}".to_string() } else { format!("{{ {} }}", body) };
+                    let width = INDENT.len() * self.depth + self.line.len() + 1 + body.len();
+                    return (width <= MAX_WIDTH).then_some((body, i));
+                }
+                TT::LCurlyB | TT::Comment => return None,
+                // only a trailing `;` is allowed
+                TT::SemiColon if self.toks.get(i + 1).is_some_and(|n| n.TT != TT::RCurlyB) => return None,
+                _ => {}
+            }
+            // several lines are several statements
+            if prev.is_some_and(|p: &Token| breaks(self.src, p.end, t.start) > 0) {
+                return None;
+            }
+            if self.space(prev, t) {
+                body.push(' ');
+            }
+            body.push_str(t.text());
+            prev = Some(t);
+        }
+        None
+    }
+
+    fn run(mut self) -> String {
+        let mut i = 0;
+        while i < self.toks.len() {
+            let t = &self.toks[i];
+            let prev = i.checked_sub(1).map(|p| &self.toks[p]);
+            let gap = prev.map_or(0, |p| breaks(self.src, p.end, t.start));
+
+            // statements need no `;`, so line breaks inside bodies are kept
+            if self.depth > 0 && gap > 0 && !matches!(t.TT, TT::Comment | TT::Else) {
+                self.flush();
+            }
+            if self.line.is_empty() && prev.is_some() {
+                // comments in front of a fn are part of it
+                let item = self.toks[i..].iter().find(|n| n.TT != TT::Comment).is_some_and(|n| matches!(n.TT, TT::FN | TT::Pub));
+                let forced = self.depth == 0 && prev.is_some_and(|p| {
+                    p.TT == TT::RCurlyB || (p.TT == TT::SemiColon && item)
+                });
+                // a trailing comment belongs to the line before it
+                if !(t.TT == TT::Comment && gap == 0) && (forced || gap >= 2) {
+                    self.blank();
+                }
+            }
+
+            match t.TT {
+                TT::Comment => {
+                    if gap == 0 && prev.is_some() {
+                        if self.line.is_empty() {
+                            self.out.pop();
+                            self.out.push(' ');
+                            self.out.push_str(t.text());
+                            self.out.push('\n');
+                        } else {
+                            self.line.push(' ');
+                            self.line.push_str(t.text());
+                            self.flush();
+                        }
+                    } else {
+                        if gap >= 2 {
+                            self.blank();
+                        }
+                        self.flush();
+                        self.line.push_str(t.text());
+                        self.flush();
+                    }
+                }
+                TT::LCurlyB => match self.one_line(i) {
+                    Some((body, close)) => {
+                        if !self.line.is_empty() {
+                            self.line.push(' ');
+                        }
+                        self.line.push_str(&body);
+                        self.last = Some(&self.toks[close]);
+                        if !self.joins(close) {
+                            self.flush();
+                        }
+                        i = close;
+                    }
+                    None => {
+                        if !self.line.is_empty() {
+                            self.line.push(' ');
+                        }
+                        self.line.push('{');
+                        self.flush();
+                        self.depth += 1;
+                    }
+                },
+                TT::RCurlyB => {
+                    self.flush();
+                    self.depth = self.depth.saturating_sub(1);
+                    self.push(t);
+                    if !self.joins(i) {
+                        self.flush();
+                    }
+                }
+                TT::SemiColon => {
+                    self.push(t);
+                    self.flush();
+                }
+                _ => self.push(t),
+            }
+            i += 1;
+        }
+        self.flush();
+        self.out
+    }
+}
+
+///`src` in canonical style
+///
+/// refuses files with characters the lexer would drop, and checks that the
+/// result lexes to exactly the same tokens before handing it out
+pub fn format(src: &[u8]) -> Result<String, FmtErr> {
+    let text = std::str::from_utf8(src).map_err(|e| FmtErr { line: breaks(src, 0, e.valid_up_to()) + 1, msg: "not valid UTF-8".into() })?;
+    let toks = lex_trivia(text);
+
+    let mut covered = 0;
+    let mut next = toks.iter().peekable();
+    for (i, &b) in src.iter().enumerate() {
+        while next.peek().is_some_and(|t| t.start <= i) {
+            covered = covered.max(next.next().map_or(0, |t| t.end));
+        }
+        if i >= covered && !b.is_ascii_whitespace() {
+            return Err(FmtErr {
+                line: breaks(src, 0, i) + 1,
+                msg: format!("unexpected character '{}'", String::from_utf8_lossy(&src[i..src.len().min(i + 4)]).chars().next().unwrap_or('?')),
+            });
+        }
+    }
+
+    let code: Vec<&Token> = toks.iter().filter(|t| t.TT != TT::Comment).collect();
+    let unary = code.iter().enumerate()
+        .filter(|(i, t)| t.TT == TT::Minus && starts_operand(i.checked_sub(1).map(|p| code[p])))
+        .map(|(_, t)| t.start)
+        .collect();
+    let out = Writer { src, toks: &toks, out: String::new(), line: String::new(), last: None, depth: 0, unary }.run();
+
+    let same = |a: &Token, b: &Token| a.TT == b.TT && a.value == b.value;
+    let again = lex_trivia(&out);
+    if again.len() != toks.len() || !toks.iter().zip(&again).all(|(a, b)| same(a, b)) {
+        return Err(FmtErr { line: 1, msg: "formatting would change the meaning of the file".into() });
+    }
+    Ok(out)
+}
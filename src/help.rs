@@ -1,13 +1,152 @@
 use colored::*;
+use crate::term::glyphs;
+
+///print `(text, is_last)` rows under a section header
+fn section(title: &str, rows: &[&str]) {
+    let g = glyphs();
+    println!("{}", format!("{}{} [{}]", g.bar, g.line, title).bold().cyan());
+    for (i, row) in rows.iter().enumerate() {
+        let branch = if i + 1 == rows.len() { g.end } else { g.tee };
+        println!("{}", format!("{}   {} {}", g.bar, branch, row).green());
+    }
+}
+
+fn header(title: &str) {
+    let g = glyphs();
+    println!("{}", format!("{}{}[{}]", g.top, g.line, title).bold().bright_magenta());
+}
+
+fn footer() {
+    let g = glyphs();
+    println!("{}", format!("{}{}", g.bot, g.line.repeat(52)).blue().bold());
+}
+
+const GLOBAL_OPTS: &[&str] = &[
+    "-q, --quiet                - Only print diagnostics",
+    "--color=auto|always|never  - When to use colors (honors NO_COLOR, CLICOLOR_FORCE)",
+    "--ascii                    - Draw boxes with plain ASCII characters",
+    "-h, --help                 - Show help (also per command: sip build --help)",
+    "-V, --version              - Print the compiler version",
+];
 
 pub fn help() {
-    println!("{}", "╭─[Sip Programming Language]".bold().bright_magenta());
-    println!("{}", "│   ├─ build   - Build a Sip source file/folder".green());
-    println!("{}", "│   └─ help    - Display this help message".green());
-    println!("{}", "│─ [USAGE]".bold().cyan());
-    println!("{}", "│   ├─ sip <command> <file/folder(not supported yet)> <options>".yellow());
-    println!("{}", "│─ [EXAMPLES]".bold().cyan());
-    println!("{}", "│   ├─ sip build my_program.sip".green());
-    println!("{}", "│   └─ sip run my_program.sip".green());
-    println!("{}", "╰────────────────────────────────────────────────────".blue().bold());
+    header("Sip Programming Language");
+    section("COMMANDS", &[
+        "build   - Build a Sip source file/folder, or the project in the current directory",
+        "run     - Build, then run `main` with the interpreter and print its result",
+        "new     - Create a new project",
+        "watch   - Rebuild whenever a source file changes",
+        "lsp     - Run the language server over stdin/stdout",
+        "fmt     - Rewrite source files in the canonical style",
+        "help    - Display this help message (sip help <command> for details)",
+        "version - Print the compiler version",
+    ]);
+    section("USAGE", &["sip <command> <file/folder> <options>"]);
+    section("OPTIONS", GLOBAL_OPTS);
+    section("EXIT CODES", &[
+        "0 - success",
+        "1 - the source has errors",
+        "2 - bad command line",
+        "3 - a file could not be read or written",
+        "4 - internal compiler error",
+        "5 - the program stopped with a runtime error (sip run)",
+    ]);
+    section("EXAMPLES", &["sip build my_program.sip", "sip help build"]);
+    footer();
+}
+
+///help for a single command, `false` if there is no such command
+pub fn help_cmd(cmd: &str) -> bool {
+    match cmd {
+        "build" => {
+            header("sip build");
+            section("USAGE", &["sip build [file/folder] <options>", "without a path, builds the project whose sip.toml is in (or above) the current directory"]);
+            section("OPTIONS", &[
+                "-o <file>          - Write the --emit=tokens|ast output to <file>",
+                "--emit=check       - What to produce (check: diagnostics only)",
+                "--emit=tokens|ast  - Print the tokens (with line:column) or the ast of every file",
+                "--json             - Print --emit=tokens|ast output as JSON",
+                "--target <name>    - Target to build for, only native is supported yet",
+                "-O[0-3]            - Optimization level, only -O0 is supported yet",
+                "--timings[=json]   - Report the time spent in each phase, per file (on stderr)",
+                "--no-cache         - Do not use the incremental cache in target/sip-cache (projects only)",
+            ]);
+            section("GLOBAL OPTIONS", GLOBAL_OPTS);
+            section("EXAMPLES", &["sip build my_program.sip", "sip build src/ --timings", "sip build main.sip --emit=ast --json", "sip build main.sip --emit=ast -o main.ast"]);
+        }
+        "run" => {
+            header("sip run");
+            section("USAGE", &["sip run [file/folder] <options>", "takes the same options as sip build, a folder runs the `main` in its main.sip"]);
+            section("BEHAVIOR", &[
+                "Runs only if the build has no errors",
+                "Prints the value `main` returns, nothing if it is declared without one",
+                "Integer overflow and division by zero stop the program with exit code 5",
+                "So do calls nested deeper than the --max-depth limit, e.g. recursion without a base case",
+            ]);
+            section("OPTIONS", &["--max-depth <n>    - Nested calls allowed before stopping (default: 1000, at most 10000)"]);
+            section("GLOBAL OPTIONS", GLOBAL_OPTS);
+            section("EXAMPLES", &["sip run my_program.sip", "sip run"]);
+        }
+        "new" => {
+            header("sip new");
+            section("USAGE", &["sip new <name>"]);
+            section("CREATES", &[
+                "<name>/sip.toml      - Project manifest (name, entry, target, opt-level, dependencies)",
+                "<name>/src/main.sip  - Entry file",
+                "<name>/.gitignore    - Ignores build output",
+            ]);
+            section("EXAMPLES", &["sip new hello && cd hello && sip build"]);
+        }
+        "watch" => {
+            header("sip watch");
+            section("USAGE", &["sip watch [file/folder] <options>", "takes the same options as sip build, Linux only"]);
+            section("BEHAVIOR", &[
+                "Builds once, then waits for .sip files or sip.toml to change",
+                "Rapid saves are batched into a single rebuild",
+                "Unchanged files come from the incremental cache",
+            ]);
+            section("GLOBAL OPTIONS", GLOBAL_OPTS);
+            section("EXAMPLES", &["sip watch", "sip watch src/ --timings"]);
+        }
+        "lsp" => {
+            header("sip lsp");
+            section("USAGE", &["sip lsp", "speaks JSON-RPC over stdin/stdout, point your editor's LSP client at it"]);
+            section("FEATURES", &[
+                "Diagnostics on open and on every change",
+                "Go to definition and hover for functions, including imported ones",
+                "Document symbols for every function",
+                "Completion of keywords, types and function names",
+            ]);
+        }
+        "fmt" => {
+            header("sip fmt");
+            section("USAGE", &["sip fmt [file/folder] <options>", "without a path, formats the project whose sip.toml is in (or above) the current directory"]);
+            section("OPTIONS", &["--check  - Do not write anything, list unformatted files and exit with 1 if there are any"]);
+            section("STYLE", &[
+                "4 spaces of indentation, one statement per line",
+                "`fn name(i32 a, i32 b) @ i32 {` - no space before `(`, one after `,`, spaces around `@`",
+                "Short bodies with a single statement stay on one line: `{ ret 0 }`",
+                "`else` stays on the line of the `}` before it: `} else {`",
+                "One blank line between functions, blank lines inside bodies are kept (at most one)",
+                "Comments are kept where they are",
+            ]);
+            section("GLOBAL OPTIONS", GLOBAL_OPTS);
+            section("EXAMPLES", &["sip fmt", "sip fmt src/ --check"]);
+        }
+        "help" => {
+            header("sip help");
+            section("USAGE", &["sip help [command]"]);
+        }
+        "version" => {
+            header("sip version");
+            section("USAGE", &["sip version", "sip --version"]);
+        }
+        _ => return false,
+    }
+    footer();
+    true
+}
+
+pub fn version() {
+    println!("sip {}", env!("CARGO_PKG_VERSION"));
 }
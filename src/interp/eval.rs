@@ -0,0 +1,234 @@
+use crate::{
+    ast::defs::{Assign, BinOp, Expr, ExprT, JumpT, Loop, LoopT, NodeT, UnOp, VarT},
+    module::resolve::{FnRef, Lookup},
+};
+
+use super::{Engine, RunErr, check_args, value};
+
+///why evaluation stopped before reaching the end of an expression or block
+enum Stop {
+    Err(RunErr),
+    ///`ret` with its value
+    Ret(VarT),
+    Break,
+    Continue,
+}
+
+impl From<RunErr> for Stop {
+    fn from(e: RunErr) -> Self {
+        Stop::Err(e)
+    }
+}
+
+///the values visible while a fn runs
+struct Frame<'a> {
+    func: &'a str,
+    module: usize,
+    ret: &'a VarT,
+    ///calls this frame is nested in, 1 for the fn called from Rust
+    depth: usize,
+    ///params and locals, inner blocks push onto the end and truncate on the way out
+    vars: Vec<(&'a str, VarT)>,
+}
+
+impl Engine {
+    ///run the fn at `r` as call number `depth` on the stack, `args` are already checked against its params
+    pub(super) fn invoke(&self, r: FnRef, args: Vec<VarT>, depth: usize) -> Result<VarT, RunErr> {
+        let f = self.at(r);
+        let names = f.args.iter().flatten().map(|a| a.name.as_str());
+        let mut frame = Frame { func: &f.name, module: r.module, ret: &f.ret.TT, depth, vars: names.zip(args).collect() };
+
+        let nil = matches!(f.ret.TT, VarT::Nil);
+        let (v, line) = match self.block(&mut frame, &f.body, Some(&f.ret.TT).filter(|_| !nil)) {
+            // whatever the body of a fn returning nothing ends in is dropped
+            Ok(_) if nil => (VarT::Nil, f.line),
+            Ok(Some(v)) => (v, f.line),
+            Ok(None) | Err(Stop::Break | Stop::Continue) => return Err(RunErr::NoValue(f.name.clone())),
+            Err(Stop::Ret(v)) => (v, f.line),
+            Err(Stop::Err(e)) => return Err(e),
+        };
+        if !v.same_type(&f.ret.TT) {
+            return Err(RunErr::Type { expected: f.ret.TT.name(), found: v.name(), line });
+        }
+        Ok(v)
+    }
+
+    ///run the statements of `body`, its value if it ends in an expression
+    fn block<'a>(&self, fr: &mut Frame<'a>, body: &'a [NodeT], hint: Option<&VarT>) -> Result<Option<VarT>, Stop> {
+        let mark = fr.vars.len();
+        let mut out = None;
+        for (i, node) in body.iter().enumerate() {
+            let last = i + 1 == body.len();
+            match node {
+                NodeT::RetNode(r) => {
+                    let v = match &r.value {
+                        Some(v) => self.eval(fr, v, Some(fr.ret))?,
+                        None if matches!(fr.ret, VarT::Nil) => VarT::Nil,
+                        None => return Err(RunErr::NoValue(fr.func.to_string()).into()),
+                    };
+                    return Err(Stop::Ret(v));
+                }
+                NodeT::ExprNode(e) => {
+                    let v = self.stmt(fr, e, if last { hint } else { None })?;
+                    if last {
+                        out = v;
+                    }
+                }
+                NodeT::LoopNode(l) => self.r#loop(fr, l)?,
+                NodeT::JumpNode(j) => return Err(if j.kind == JumpT::Break { Stop::Break } else { Stop::Continue }),
+                NodeT::LetNode(l) => {
+                    let v = self.eval(fr, &l.value, Some(&l.var.TT))?;
+                    fr.vars.push((&l.var.name, v));
+                }
+                NodeT::AssignNode(a) => self.assign(fr, a)?,
+                NodeT::FUNCNode(_) | NodeT::ImportNode(_) | NodeT::ConstNode(_) | NodeT::GlobalNode(_) => {}
+            }
+        }
+        fr.vars.truncate(mark);
+        Ok(out)
+    }
+
+    ///an expression statement, only an `if` may have no value
+    fn stmt<'a>(&self, fr: &mut Frame<'a>, e: &'a Expr, hint: Option<&VarT>) -> Result<Option<VarT>, Stop> {
+        match &e.kind {
+            ExprT::If(c, then, els) => {
+                let body = if value::as_bool(&self.eval(fr, c, None)?, c.line)? { Some(then) } else { els.as_ref() };
+                match body {
+                    Some(b) => self.block(fr, b, hint),
+                    None => Ok(None),
+                }
+            }
+            _ => self.eval(fr, e, hint).map(Some),
+        }
+    }
+
+    fn assign<'a>(&self, fr: &mut Frame<'a>, a: &'a Assign) -> Result<(), Stop> {
+        let ExprT::Ident(name) = &a.target.kind else { return Ok(()) };
+        let old = self.eval(fr, &a.target, None)?;
+        let mut v = self.eval(fr, &a.value, Some(&old))?;
+        if let Some(op) = a.op {
+            v = value::binary(op, &old, &v, a.line)?;
+        }
+        if !v.same_type(&old) {
+            return Err(RunErr::Type { expected: old.name(), found: v.name(), line: a.line }.into());
+        }
+        if let Some(slot) = fr.vars.iter_mut().rev().find(|(n, _)| n == name) {
+            slot.1 = v;
+        } else if let Some(slot) = self.globals.lock().unwrap()[fr.module].get_mut(name) {
+            *slot = v;
+        }
+        Ok(())
+    }
+
+    fn r#loop<'a>(&self, fr: &mut Frame<'a>, l: &'a Loop) -> Result<(), Stop> {
+        let mark = fr.vars.len();
+        let (mut i, to) = match &l.kind {
+            LoopT::For { from, to, .. } => {
+                let (a, b) = self.pair(fr, from, to, None)?;
+                (Some(a), Some(b))
+            }
+            _ => (None, None),
+        };
+        loop {
+            match &l.kind {
+                LoopT::While(c) if !value::as_bool(&self.eval(fr, c, None)?, c.line)? => break,
+                LoopT::For { var, .. } => {
+                    let (Some(x), Some(to)) = (&i, &to) else { break };
+                    if !value::compare(BinOp::Lt, x, to, l.line)? {
+                        break;
+                    }
+                    fr.vars.push((&var.name, x.clone()));
+                    // `x < to`, so the step cannot overflow
+                    let one = value::literal("1", Some(x), l.line)?;
+                    i = Some(value::binary(BinOp::Add, x, &one, l.line)?);
+                }
+                _ => {}
+            }
+            let r = self.block(fr, &l.body, None);
+            fr.vars.truncate(mark);
+            match r {
+                Ok(_) | Err(Stop::Continue) => {}
+                Err(Stop::Break) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    ///values of two operands, a literal on the left takes its type from the right
+    fn pair<'a>(&self, fr: &mut Frame<'a>, l: &'a Expr, r: &'a Expr, hint: Option<&VarT>) -> Result<(VarT, VarT), Stop> {
+        if l.is_untyped() && !r.is_untyped() {
+            let rv = self.eval(fr, r, hint)?;
+            Ok((self.eval(fr, l, Some(&rv))?, rv))
+        } else {
+            let lv = self.eval(fr, l, hint)?;
+            let rv = self.eval(fr, r, Some(&lv))?;
+            Ok((lv, rv))
+        }
+    }
+
+    ///value of `e`, untyped literals take the type of `hint`
+    fn eval<'a>(&self, fr: &mut Frame<'a>, e: &'a Expr, hint: Option<&VarT>) -> Result<VarT, Stop> {
+        Ok(match &e.kind {
+            ExprT::Num(n) => value::literal(n, hint, e.line)?,
+            ExprT::Bool(b) => VarT::Bool(*b),
+            ExprT::Ident(name) => self.var(fr, name).ok_or_else(|| RunErr::UnknownName { name: name.clone(), line: e.line })?,
+            // `-128` has to be read as one literal to fit the smallest value of its type
+            ExprT::Unary(UnOp::Neg, x) if let ExprT::Num(n) = &x.kind => value::literal(&format!("-{}", n), hint, e.line)?,
+            ExprT::Unary(op, x) => value::unary(*op, &self.eval(fr, x, hint)?, e.line)?,
+            ExprT::Binary(op @ (BinOp::And | BinOp::Or), l, r) => {
+                let lv = value::as_bool(&self.eval(fr, l, None)?, l.line)?;
+                // `false && ..` and `true || ..` are decided by the left side alone
+                if lv == (*op == BinOp::Or) {
+                    VarT::Bool(lv)
+                } else {
+                    VarT::Bool(value::as_bool(&self.eval(fr, r, None)?, r.line)?)
+                }
+            }
+            ExprT::Binary(op, l, r) => {
+                // the operands of a comparison are not of the type it produces
+                let (lv, rv) = self.pair(fr, l, r, hint.filter(|_| op.is_arith()))?;
+                value::binary(*op, &lv, &rv, e.line)?
+            }
+            ExprT::Call(path, args) => self.call_path(fr, path, args, e.line)?,
+            ExprT::Cast(x, ty) => value::cast(&self.eval(fr, x, None)?, ty),
+            ExprT::If(..) => self.stmt(fr, e, hint)?.ok_or_else(|| RunErr::NoValue(fr.func.to_string()))?,
+        })
+    }
+
+    ///value of the name `name`: a local, else a const or global of the current module
+    fn var(&self, fr: &Frame, name: &str) -> Option<VarT> {
+        match fr.vars.iter().rev().find(|(n, _)| *n == name) {
+            Some((_, v)) => Some(v.clone()),
+            None => self.consts[fr.module].get(name).cloned().or_else(|| self.globals.lock().unwrap()[fr.module].get(name).cloned()),
+        }
+    }
+
+    ///a call from sip code: a sip fn visible from the current module, else a host fn
+    fn call_path<'a>(&self, fr: &mut Frame<'a>, path: &[String], args: &'a [Expr], line: usize) -> Result<VarT, Stop> {
+        let segs: Vec<&str> = path.iter().map(String::as_str).collect();
+        let name = path.join(".");
+        if let Lookup::Found(r) = self.resolver.lookup(fr.module, &segs) {
+            let params = self.at(r).args.as_deref().unwrap_or_default();
+            let vals = self.eval_args(fr, args, params.iter().map(|p| &p.TT))?;
+            check_args(&name, params.iter().map(|p| &p.TT), &vals)?;
+            if fr.depth >= self.max_depth {
+                return Err(RunErr::Depth { func: name, limit: self.max_depth, line }.into());
+            }
+            return Ok(self.invoke(r, vals, fr.depth + 1)?);
+        }
+        let host = self.host.get(&name).ok_or_else(|| RunErr::UnknownFn(name.clone()))?;
+        let vals = self.eval_args(fr, args, host.params.iter())?;
+        check_args(&name, host.params.iter(), &vals)?;
+        let v = (host.f)(&vals).map_err(|msg| RunErr::Host { func: name.clone(), msg })?;
+        if !v.same_type(&host.ret) {
+            return Err(RunErr::Host { func: name, msg: format!("returned {} instead of {}", v.name(), host.ret.name()) }.into());
+        }
+        Ok(v)
+    }
+
+    fn eval_args<'a, 'p>(&self, fr: &mut Frame<'a>, args: &'a [Expr], params: impl Iterator<Item = &'p VarT>) -> Result<Vec<VarT>, Stop> {
+        let mut params = params;
+        args.iter().map(|a| self.eval(fr, a, params.next())).collect()
+    }
+}
@@ -0,0 +1,270 @@
+pub mod eval;
+pub mod value;
+
+use std::{collections::HashMap, fmt, path::Path, sync::Mutex};
+
+use crate::{
+    ast::defs::{FUNC, NodeT, VarT},
+    driver::Project,
+    err::def::PErr,
+    module::{graph::ModGraph, resolve::{FnRef, Lookup, Resolver}},
+    sema,
+};
+
+///a Rust closure sip code can call
+pub type HostFn = Box<dyn Fn(&[VarT]) -> Result<VarT, String> + Send + Sync>;
+
+///a registered host fn and its signature
+pub struct Host {
+    pub params: Vec<VarT>,
+    pub ret: VarT,
+    f: HostFn,
+}
+
+///why a call into sip failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunErr {
+    ///no sip or host fn with this name
+    UnknownFn(String),
+    Arity { func: String, expected: usize, found: usize },
+    ///argument `index` of `func` has the wrong type
+    ArgType { func: String, index: usize, expected: &'static str, found: &'static str },
+    ///a value of the wrong type, at a source line
+    Type { expected: &'static str, found: &'static str, line: usize },
+    UnknownName { name: String, line: usize },
+    ///a number literal that is not a valid value of its type
+    Literal { text: String, ty: &'static str, line: usize },
+    Overflow { line: usize },
+    DivZero { line: usize },
+    ///`func` ended without producing a value
+    NoValue(String),
+    ///a call at `line` would nest more than `limit` calls deep
+    Depth { func: String, limit: usize, line: usize },
+    ///a host fn returned an error
+    Host { func: String, msg: String },
+    ///a `VarT` handed back to Rust is not the type asked for
+    Convert { expected: &'static str, found: &'static str },
+}
+
+impl fmt::Display for RunErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunErr::UnknownFn(n) => write!(f, "no function named '{}'", n),
+            RunErr::Arity { func, expected, found } => write!(f, "'{}' takes {} argument(s), got {}", func, expected, found),
+            RunErr::ArgType { func, index, expected, found } => {
+                write!(f, "argument {} of '{}' must be {}, got {}", index + 1, func, expected, found)
+            }
+            RunErr::Type { expected, found, line } => write!(f, "line {}: expected {}, got {}", line, expected, found),
+            RunErr::UnknownName { name, line } => write!(f, "line {}: unknown name '{}'", line, name),
+            RunErr::Literal { text, ty, line } => write!(f, "line {}: '{}' is not a valid {}", line, text, ty),
+            RunErr::Overflow { line } => write!(f, "line {}: arithmetic overflow", line),
+            RunErr::DivZero { line } => write!(f, "line {}: division by zero", line),
+            RunErr::NoValue(n) => write!(f, "'{}' ended without a value", n),
+            RunErr::Depth { func, limit, line } => {
+                write!(f, "line {}: stack overflow, calling '{}' would nest more than {} calls", line, func, limit)
+            }
+            RunErr::Host { func, msg } => write!(f, "host function '{}' failed: {}", func, msg),
+            RunErr::Convert { expected, found } => write!(f, "expected a {} result, got {}", expected, found),
+        }
+    }
+}
+
+impl std::error::Error for RunErr {}
+
+///default for `Engine::set_max_depth`
+pub const MAX_DEPTH: usize = 1000;
+///highest depth limit `Engine::set_max_depth` takes, its stack (`DEPTH_CAP * STACK_PER_CALL`, 625 MiB) can still be reserved
+pub const DEPTH_CAP: usize = 10_000;
+///native stack a single sip call may take, with room to spare for unoptimized builds
+const STACK_PER_CALL: usize = 64 * 1024;
+
+///runs sip code inside a Rust program
+///
+/// sip fns are called by name with `VarT` arguments, which convert from and to
+/// the matching Rust types (`i32`, `i64`, `u8`, `f32`, `f64`). Rust closures
+/// registered with `register` can be called from sip like any other fn.
+pub struct Engine {
+    ///ast per module, in the same order as the resolver's namespaces
+    modules: Vec<Vec<NodeT>>,
+    resolver: Resolver,
+    ///the module names passed to `call` are looked up in
+    entry: usize,
+    host: HashMap<String, Host>,
+    ///folded consts per module
+    consts: Vec<HashMap<String, VarT>>,
+    ///globals per module, they keep their values between calls
+    globals: Mutex<Vec<HashMap<String, VarT>>>,
+    ///calls nested deeper than this fail with `RunErr::Depth`
+    max_depth: usize,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self {
+            modules: Vec::new(),
+            resolver: Resolver::default(),
+            entry: 0,
+            host: HashMap::new(),
+            consts: Vec::new(),
+            globals: Mutex::default(),
+            max_depth: MAX_DEPTH,
+        }
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///replace the loaded code with `src`, a single module without imports
+    ///
+    /// host fns should be registered first, so calls to them are not taken for typos
+    pub fn load(&mut self, src: &str) -> Result<(), Vec<PErr>> {
+        let mut p = crate::parse(src);
+        let modules = vec![p.get_ast().clone()];
+        let graph = ModGraph { names: vec![String::new()], edges: vec![Vec::new()] };
+        let resolver = Resolver::from_asts(&graph, &modules);
+        let externs: Vec<sema::Extern> = self.host.iter()
+            .map(|(name, h)| sema::Extern { name: name.clone(), params: h.params.clone(), ret: h.ret.clone() })
+            .collect();
+        let (binds, errs) = sema::resolve(&resolver, 0, &modules[0], &externs);
+        p.errs.extend(errs);
+        p.errs.extend(sema::check(&[&modules[0]], 0, &binds, &externs));
+        if p.erroccur() {
+            p.errs.sort_by_key(|e| (e.line, e.start));
+            return Err(p.errs);
+        }
+        self.resolver = resolver;
+        self.modules = modules;
+        self.entry = 0;
+        self.consts = vec![binds.consts];
+        self.globals = Mutex::new(vec![binds.globals]);
+        Ok(())
+    }
+
+    ///take the code of a checked project, names passed to `call` are looked up in `entry`
+    pub fn from_project(project: &Project, entry: &Path) -> Self {
+        let modules: Vec<Vec<NodeT>> = project.units.iter().map(|u| u.parser.get_ast().clone()).collect();
+        let entry = project.units.iter().position(|u| u.path == entry).unwrap_or(0);
+        let resolver = Resolver::from_asts(&project.graph, &modules);
+        let consts = project.units.iter().map(|u| u.binds.consts.clone()).collect();
+        let globals = Mutex::new(project.units.iter().map(|u| u.binds.globals.clone()).collect());
+        Self { modules, resolver, entry, consts, globals, ..Self::default() }
+    }
+
+    ///make `f` callable from sip as `name`, taking values of the types in `params`
+    pub fn register<F>(&mut self, name: &str, params: &[VarT], ret: VarT, f: F)
+    where
+        F: Fn(&[VarT]) -> Result<VarT, String> + Send + Sync + 'static,
+    {
+        self.host.insert(name.to_string(), Host { params: params.to_vec(), ret, f: Box::new(f) });
+    }
+
+    ///fail calls nested more than `depth` deep instead of running out of stack, `MAX_DEPTH` by default
+    ///
+    /// `depth` is clamped to `DEPTH_CAP`, deeper stacks could not be reserved up front
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = depth.min(DEPTH_CAP);
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    ///native stack `call` may need, enough for calls up to the depth limit
+    ///
+    /// calls are evaluated recursively on the native stack, so the default stack
+    /// of a thread only holds a few hundred nested calls
+    pub fn stack_size(&self) -> usize {
+        self.max_depth * STACK_PER_CALL
+    }
+
+    fn at(&self, r: FnRef) -> &FUNC {
+        match &self.modules[r.module][r.node] {
+            NodeT::FUNCNode(f) => f,
+            _ => unreachable!("resolver points at a non-fn node"),
+        }
+    }
+
+    ///the fn `call` would run for `name`
+    pub fn func(&self, name: &str) -> Option<&FUNC> {
+        match self.resolver.lookup(self.entry, &[name]) {
+            Lookup::Found(r) => Some(self.at(r)),
+            _ => None,
+        }
+    }
+
+    ///call the sip fn `name`, arguments must match its parameter types exactly
+    ///
+    /// the call runs on the caller's thread and evaluates nested calls on its native
+    /// stack, up to `stack_size` bytes of it. Deep recursion needs a thread built with
+    /// at least that much (`thread::Builder::stack_size`), else a smaller depth limit.
+    pub fn call(&self, name: &str, args: &[VarT]) -> Result<VarT, RunErr> {
+        let r = match self.resolver.lookup(self.entry, &[name]) {
+            Lookup::Found(r) => r,
+            _ => return Err(RunErr::UnknownFn(name.to_string())),
+        };
+        check_args(name, self.at(r).args.as_deref().unwrap_or_default().iter().map(|a| &a.TT), args)?;
+        self.invoke(r, args.to_vec(), 1)
+    }
+}
+
+///`args` against the parameter types of `func`
+fn check_args<'a>(func: &str, params: impl ExactSizeIterator<Item = &'a VarT>, args: &[VarT]) -> Result<(), RunErr> {
+    if params.len() != args.len() {
+        return Err(RunErr::Arity { func: func.to_string(), expected: params.len(), found: args.len() });
+    }
+    for (index, (p, a)) in params.zip(args).enumerate() {
+        if !p.same_type(a) {
+            return Err(RunErr::ArgType { func: func.to_string(), index, expected: p.name(), found: a.name() });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(src: &str) -> Engine {
+        let mut e = Engine::new();
+        if let Err(errs) = e.load(src) {
+            panic!("{:?}", errs);
+        }
+        e
+    }
+
+    #[test]
+    fn mutual_recursion() {
+        let e = engine("fn even(i32 n) @ bool { if n == 0 { true } else { odd(n - 1) } }\nfn odd(i32 n) @ bool { if n == 0 { false } else { even(n - 1) } }");
+        assert!(matches!(e.call("even", &[VarT::I32(10)]), Ok(VarT::Bool(true))));
+        assert!(matches!(e.call("odd", &[VarT::I32(10)]), Ok(VarT::Bool(false))));
+    }
+
+    #[test]
+    fn endless_recursion_hits_the_depth_limit() {
+        let e = engine("fn f(i32 n) @ i32 { f(n + 1) }");
+        // a test thread has 2 MiB of stack, far less than the depth limit needs
+        let r = std::thread::scope(|s| {
+            std::thread::Builder::new().stack_size(e.stack_size()).spawn_scoped(s, || e.call("f", &[VarT::I32(0)])).unwrap().join().unwrap()
+        });
+        assert!(matches!(r, Err(RunErr::Depth { limit: MAX_DEPTH, .. })), "{:?}", r);
+    }
+
+    #[test]
+    fn depth_limit_is_configurable() {
+        let mut e = engine("fn down(i32 n) @ i32 { if n == 0 { 0 } else { down(n - 1) + 1 } }");
+        e.set_max_depth(10);
+        assert!(matches!(e.call("down", &[VarT::I32(9)]), Ok(VarT::I32(9))));
+        assert!(matches!(e.call("down", &[VarT::I32(10)]), Err(RunErr::Depth { limit: 10, .. })));
+    }
+
+    #[test]
+    fn depth_limit_is_capped() {
+        let mut e = Engine::new();
+        e.set_max_depth(usize::MAX);
+        assert_eq!(e.max_depth(), DEPTH_CAP);
+        assert_eq!(e.stack_size(), DEPTH_CAP * STACK_PER_CALL);
+    }
+}
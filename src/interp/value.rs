@@ -0,0 +1,170 @@
+use crate::ast::defs::{BinOp, UnOp, VarT};
+
+use super::RunErr;
+
+macro_rules! conv {
+    ($($t:ty => $v:ident),*) => {$(
+        impl From<$t> for VarT {
+            fn from(v: $t) -> Self {
+                VarT::$v(v)
+            }
+        }
+
+        impl TryFrom<VarT> for $t {
+            type Error = RunErr;
+
+            fn try_from(v: VarT) -> Result<Self, RunErr> {
+                match v {
+                    VarT::$v(x) => Ok(x),
+                    other => Err(RunErr::Convert { expected: stringify!($t), found: other.name() }),
+                }
+            }
+        }
+    )*};
+}
+
+conv!(i32 => I32, i64 => I64, u8 => U8, f32 => F32, f64 => F64, bool => Bool);
+
+///`text` read as a value of the type of `hint`, `i32` or `f64` without one
+pub fn literal(text: &str, hint: Option<&VarT>, line: usize) -> Result<VarT, RunErr> {
+    let float = text.contains('.');
+    let hint = match hint {
+        Some(h) => h.clone(),
+        None if float => VarT::F64(0.0),
+        None => VarT::I32(0),
+    };
+    let bad = || RunErr::Literal { text: text.to_string(), ty: hint.name(), line };
+    if float && !matches!(hint, VarT::F32(_) | VarT::F64(_)) {
+        return Err(bad());
+    }
+    Ok(match hint {
+        VarT::I32(_) => VarT::I32(text.parse().map_err(|_| bad())?),
+        VarT::I64(_) => VarT::I64(text.parse().map_err(|_| bad())?),
+        VarT::U8(_) => VarT::U8(text.parse().map_err(|_| bad())?),
+        VarT::F32(_) => VarT::F32(text.parse().map_err(|_| bad())?),
+        VarT::F64(_) => VarT::F64(text.parse().map_err(|_| bad())?),
+        VarT::Bool(_) | VarT::Nil => return Err(bad()),
+    })
+}
+
+macro_rules! int_op {
+    ($op:expr, $a:expr, $b:expr, $v:ident, $line:expr) => {{
+        let r = match $op {
+            BinOp::Add => $a.checked_add($b),
+            BinOp::Sub => $a.checked_sub($b),
+            BinOp::Mul => $a.checked_mul($b),
+            BinOp::Div | BinOp::Rem if $b == 0 => return Err(RunErr::DivZero { line: $line }),
+            BinOp::Div => $a.checked_div($b),
+            BinOp::Rem => $a.checked_rem($b),
+            _ => unreachable!("`{}` is not arithmetic", $op.symbol()),
+        };
+        r.map(VarT::$v).ok_or(RunErr::Overflow { line: $line })
+    }};
+}
+
+macro_rules! float_op {
+    ($op:expr, $a:expr, $b:expr, $v:ident) => {
+        Ok(VarT::$v(match $op {
+            BinOp::Add => $a + $b,
+            BinOp::Sub => $a - $b,
+            BinOp::Mul => $a * $b,
+            BinOp::Div => $a / $b,
+            BinOp::Rem => $a % $b,
+            _ => unreachable!("`{}` is not arithmetic", $op.symbol()),
+        }))
+    };
+}
+
+///`a op b`, integers fail on overflow and division by zero instead of wrapping
+///
+/// `&&` and `||` take both sides here, short-circuiting is up to the caller
+pub fn binary(op: BinOp, a: &VarT, b: &VarT, line: usize) -> Result<VarT, RunErr> {
+    if op.is_cmp() {
+        return compare(op, a, b, line).map(VarT::Bool);
+    }
+    match (a, b) {
+        (VarT::I32(a), VarT::I32(b)) if op.is_arith() => int_op!(op, *a, *b, I32, line),
+        (VarT::I64(a), VarT::I64(b)) if op.is_arith() => int_op!(op, *a, *b, I64, line),
+        (VarT::U8(a), VarT::U8(b)) if op.is_arith() => int_op!(op, *a, *b, U8, line),
+        (VarT::F32(a), VarT::F32(b)) if op.is_arith() => float_op!(op, a, b, F32),
+        (VarT::F64(a), VarT::F64(b)) if op.is_arith() => float_op!(op, a, b, F64),
+        (VarT::Bool(a), VarT::Bool(b)) if op == BinOp::And => Ok(VarT::Bool(*a && *b)),
+        (VarT::Bool(a), VarT::Bool(b)) if op == BinOp::Or => Ok(VarT::Bool(*a || *b)),
+        _ if a.same_type(b) => Err(RunErr::Type { expected: if op.is_arith() { "a number" } else { "bool" }, found: a.name(), line }),
+        _ => Err(RunErr::Type { expected: a.name(), found: b.name(), line }),
+    }
+}
+
+///`a op b` for a comparison `op` and two values of the same type
+pub fn compare(op: BinOp, a: &VarT, b: &VarT, line: usize) -> Result<bool, RunErr> {
+    macro_rules! cmp {
+        ($a:expr, $b:expr) => {
+            match op {
+                BinOp::Eq => $a == $b,
+                BinOp::Ne => $a != $b,
+                BinOp::Lt => $a < $b,
+                BinOp::Le => $a <= $b,
+                BinOp::Gt => $a > $b,
+                BinOp::Ge => $a >= $b,
+                _ => unreachable!("`{}` is not a comparison", op.symbol()),
+            }
+        };
+    }
+    match (a, b) {
+        (VarT::I32(a), VarT::I32(b)) => Ok(cmp!(a, b)),
+        (VarT::I64(a), VarT::I64(b)) => Ok(cmp!(a, b)),
+        (VarT::U8(a), VarT::U8(b)) => Ok(cmp!(a, b)),
+        (VarT::F32(a), VarT::F32(b)) => Ok(cmp!(a, b)),
+        (VarT::F64(a), VarT::F64(b)) => Ok(cmp!(a, b)),
+        (VarT::Bool(a), VarT::Bool(b)) => Ok(cmp!(a, b)),
+        _ => Err(RunErr::Type { expected: a.name(), found: b.name(), line }),
+    }
+}
+
+pub fn unary(op: UnOp, v: &VarT, line: usize) -> Result<VarT, RunErr> {
+    let overflow = RunErr::Overflow { line };
+    match op {
+        UnOp::Not => Ok(VarT::Bool(!as_bool(v, line)?)),
+        UnOp::Neg => match v {
+            VarT::I32(x) => x.checked_neg().map(VarT::I32).ok_or(overflow),
+            VarT::I64(x) => x.checked_neg().map(VarT::I64).ok_or(overflow),
+            VarT::U8(x) => x.checked_neg().map(VarT::U8).ok_or(overflow),
+            VarT::F32(x) => Ok(VarT::F32(-x)),
+            VarT::F64(x) => Ok(VarT::F64(-x)),
+            VarT::Bool(_) | VarT::Nil => Err(RunErr::Type { expected: "a number", found: v.name(), line }),
+        },
+    }
+}
+
+///`v as ty`, with the semantics of Rust's `as` (integers wrap, floats saturate)
+pub fn cast(v: &VarT, ty: &VarT) -> VarT {
+    macro_rules! to {
+        ($x:expr) => {
+            match ty {
+                VarT::I32(_) => VarT::I32($x as i32),
+                VarT::I64(_) => VarT::I64($x as i64),
+                VarT::U8(_) => VarT::U8($x as u8),
+                VarT::F32(_) => VarT::F32($x as f32),
+                VarT::F64(_) => VarT::F64($x as f64),
+                VarT::Bool(_) | VarT::Nil => v.clone(),
+            }
+        };
+    }
+    match *v {
+        VarT::I32(x) => to!(x),
+        VarT::I64(x) => to!(x),
+        VarT::U8(x) => to!(x),
+        VarT::F32(x) => to!(x),
+        VarT::F64(x) => to!(x),
+        VarT::Bool(x) => to!(x as u8),
+        VarT::Nil => VarT::Nil,
+    }
+}
+
+///the value of a condition, which has to be a `bool`
+pub fn as_bool(v: &VarT, line: usize) -> Result<bool, RunErr> {
+    match v {
+        VarT::Bool(b) => Ok(*b),
+        _ => Err(RunErr::Type { expected: "bool", found: v.name(), line }),
+    }
+}
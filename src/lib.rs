@@ -0,0 +1,57 @@
+//! The sip compiler as a library.
+//!
+//! `lex` turns source text into tokens, `Parser` turns tokens into `NodeT`s and
+//! collects `PErr`s, and `diserr` renders a diagnostic against the source it came
+//! from. `parse` does all of it in one call, on raw bytes that it checks are UTF-8.
+//! `Engine` runs the parsed code and lets Rust and sip call each other.
+//!
+//! Everything re-exported at the crate root is the stable API. The modules are
+//! public so the `sip` binary can drive them, but may change between releases.
+//! The ones only the binary uses are hidden from the docs, and `watch` and `lsp`
+//! are only built with the `cli` feature (on by default).
+
+#[doc(hidden)]
+pub mod help;
+pub mod token;
+pub mod ast;
+pub mod parser;
+pub mod err;
+#[doc(hidden)]
+pub mod term;
+#[doc(hidden)]
+pub mod cli;
+pub mod json;
+pub mod timings;
+pub mod driver;
+pub mod module;
+pub mod manifest;
+pub mod cache;
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod watch;
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod lsp;
+pub mod fmt;
+pub mod interp;
+pub mod sema;
+
+pub use ast::defs::{FUNC, Import, NodeT, Var, VarT};
+pub use err::{def::{ErrT, PErr}, edis::d1::{describe, diserr}};
+pub use interp::{Engine, RunErr};
+pub use parser::Parser;
+pub use token::{defs::{IDType, TT, Token}, lexer::{lex, lex_trivia}};
+
+///lex and parse `src`, the ast is in `get_ast()` and the errors in `errs`
+///
+/// bytes that are not UTF-8 give an empty ast and a single error at the first bad one
+pub fn parse(src: impl AsRef<[u8]>) -> Parser {
+    let src = src.as_ref();
+    let text = match std::str::from_utf8(src) {
+        Ok(t) => t,
+        Err(e) => return Parser::not_utf8(src, e),
+    };
+    let mut p = Parser::new(lex(text));
+    p.parse();
+    p
+}
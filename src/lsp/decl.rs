@@ -0,0 +1,42 @@
+use crate::ast::defs::{Expr, ExprT, LoopT, NodeT, Var};
+
+///the param, `let` or loop variable whose name starts at `start`, with how to show it
+pub fn local(ast: &[NodeT], start: usize) -> Option<(String, &Var)> {
+    ast.iter().find_map(|n| in_node(n, start))
+}
+
+fn in_node(node: &NodeT, start: usize) -> Option<(String, &Var)> {
+    match node {
+        NodeT::FUNCNode(f) => f.args.iter().flatten().find(|a| a.start == start)
+            .map(|a| (format!("{} {}", a.TT.name(), a.name), a))
+            .or_else(|| local(&f.body, start)),
+        NodeT::LetNode(l) if l.var.start == start => {
+            Some((format!("let {}{}: {}", if l.var.dyna { "mut " } else { "" }, l.var.name, l.var.TT.name()), &l.var))
+        }
+        NodeT::LetNode(l) => in_expr(&l.value, start),
+        NodeT::LoopNode(l) => match &l.kind {
+            LoopT::For { var, .. } if var.start == start => Some((format!("{} {}", var.TT.name(), var.name), var)),
+            LoopT::For { from, to, .. } => in_expr(from, start).or_else(|| in_expr(to, start)),
+            LoopT::While(c) => in_expr(c, start),
+            LoopT::Forever => None,
+        }
+        .or_else(|| local(&l.body, start)),
+        NodeT::ExprNode(e) => in_expr(e, start),
+        NodeT::RetNode(r) => r.value.as_ref().and_then(|v| in_expr(v, start)),
+        NodeT::AssignNode(a) => in_expr(&a.value, start),
+        NodeT::JumpNode(_) | NodeT::ImportNode(_) | NodeT::ConstNode(_) | NodeT::GlobalNode(_) => None,
+    }
+}
+
+///locals are only declared inside the blocks of an `if`
+fn in_expr(e: &Expr, start: usize) -> Option<(String, &Var)> {
+    match &e.kind {
+        ExprT::Num(_) | ExprT::Bool(_) | ExprT::Ident(_) => None,
+        ExprT::Unary(_, x) | ExprT::Cast(x, _) => in_expr(x, start),
+        ExprT::Binary(_, l, r) => in_expr(l, start).or_else(|| in_expr(r, start)),
+        ExprT::Call(_, args) => args.iter().find_map(|a| in_expr(a, start)),
+        ExprT::If(c, then, els) => in_expr(c, start)
+            .or_else(|| local(then, start))
+            .or_else(|| els.as_deref().and_then(|els| local(els, start))),
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    ast::defs::{FUNC, NodeT},
+    json::Json,
+    parser::Parser,
+    token::{defs::{IDType, TT, Token}, lexer::lex},
+};
+
+///an open document and the front end results for its current text
+pub struct Doc {
+    pub uri: String,
+    pub text: String,
+    pub tokens: Vec<Token>,
+    pub parser: Parser,
+}
+
+impl Doc {
+    pub fn new(uri: String, text: String) -> Self {
+        let tokens = lex(&text);
+        let mut parser = Parser::new(tokens.clone());
+        parser.parse();
+        Self { uri, text, tokens, parser }
+    }
+
+    pub fn funcs(&self) -> impl Iterator<Item = &FUNC> {
+        self.parser.get_ast().iter().filter_map(|n| match n {
+            NodeT::FUNCNode(f) => Some(f),
+            _ => None,
+        })
+    }
+
+    ///identifier token under a byte offset
+    pub fn ident_at(&self, off: usize) -> Option<&Token> {
+        self.tokens.iter().find(|t| t.start <= off && off <= t.end && t.TT == TT::Ident(IDType::Nil))
+    }
+
+    ///start of the dotted path `tok` is part of, where its use is bound: `util` in `util.add`
+    pub fn path_start(&self, tok: &Token) -> usize {
+        let ident = |i: usize| self.tokens[i].TT == TT::Ident(IDType::Nil);
+        let Some(mut i) = self.tokens.iter().position(|t| t.start == tok.start) else { return tok.start };
+        while i >= 2 && self.tokens[i - 1].TT == TT::Dot && ident(i - 2) {
+            i -= 2;
+        }
+        self.tokens[i].start
+    }
+}
+
+///LSP position (line, UTF-16 column) of a byte offset
+pub fn position(text: &str, off: usize) -> Json {
+    let off = off.min(text.len());
+    let before = &text[..text.floor_char_boundary(off)];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let col: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    Json::obj([("line", Json::Num(line as f64)), ("character", Json::Num(col as f64))])
+}
+
+///byte offset of an LSP position
+pub fn offset(text: &str, pos: &Json) -> Option<usize> {
+    let line = pos.get("line")?.as_usize()?;
+    let col = pos.get("character")?.as_usize()?;
+    let start = if line == 0 {
+        0
+    } else {
+        text.match_indices('\n').nth(line - 1)?.0 + 1
+    };
+    let mut units = 0;
+    for (i, c) in text[start..].char_indices() {
+        if units >= col || c == '\n' {
+            return Some(start + i);
+        }
+        units += c.len_utf16();
+    }
+    Some(text.len())
+}
+
+pub fn range(text: &str, start: usize, end: usize) -> Json {
+    Json::obj([("start", position(text, start)), ("end", position(text, end.max(start)))])
+}
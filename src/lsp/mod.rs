@@ -0,0 +1,466 @@
+pub mod decl;
+pub mod doc;
+pub mod rpc;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use doc::{Doc, offset, range};
+use rpc::{error, notification, read_msg, response, write_msg};
+
+use crate::{
+    ast::defs::NodeT,
+    cli::defs::Exit,
+    err::{def::PErr, edis::d1::describe},
+    json::Json,
+    manifest::{self, Manifest, deps},
+    module::{graph::ModGraph, layout::Layout, resolve::Resolver},
+    sema::{self, Bindings, Decl},
+    token::lexer::{KEYWORDS, TYPE_NAMES},
+};
+
+///LSP `SymbolKind` / `CompletionItemKind` values used here
+const SYMBOL_FUNCTION: f64 = 12.0;
+const COMPLETION_FUNCTION: f64 = 3.0;
+const COMPLETION_KEYWORD: f64 = 14.0;
+const COMPLETION_TYPE: f64 = 25.0;
+
+///JSON-RPC error for a body that is not JSON
+const PARSE_ERROR: i32 = -32700;
+///JSON-RPC error for unknown methods
+const METHOD_NOT_FOUND: i32 = -32601;
+
+///`file:///a%20b/c.sip` -> `/a b/c.sip`
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let raw = uri.strip_prefix("file://")?.as_bytes();
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'%' && let Some(b) = raw.get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+        {
+            out.push(b);
+            i += 3;
+            continue;
+        }
+        out.push(raw[i]);
+        i += 1;
+    }
+    String::from_utf8(out).ok().map(PathBuf::from)
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for b in path.to_string_lossy().bytes() {
+        if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+            uri.push(b as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", b));
+        }
+    }
+    uri
+}
+
+///where the imports of `file` point, using the project manifest when there is one
+fn layout_for(file: &Path) -> Layout {
+    let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+    manifest::find(&dir)
+        .and_then(|d| Manifest::load(&d).ok())
+        .and_then(|m| deps::resolve(m).ok())
+        .map(|g| g.layout())
+        .unwrap_or_else(|| Layout::single(dir))
+}
+
+///the declaration a name refers to: the document it lives in, how to show it and its span
+struct Found {
+    uri: String,
+    text: String,
+    detail: String,
+    start: usize,
+    end: usize,
+}
+
+///a document with the modules it imports, names bound across them
+struct View {
+    ///the document first, then its imports
+    asts: Vec<Vec<NodeT>>,
+    ///uri and text per module, same order as `asts`
+    sources: Vec<(String, String)>,
+    binds: Bindings,
+    ///name resolution and type errors of the document
+    errs: Vec<PErr>,
+}
+
+struct Server {
+    docs: HashMap<String, Doc>,
+    ///`view` per open document, dropped whenever any document changes since it may be imported by the others
+    views: RefCell<HashMap<String, Rc<View>>>,
+    shutdown: bool,
+}
+
+impl Server {
+    fn new() -> Self {
+        Self { docs: HashMap::new(), views: RefCell::new(HashMap::new()), shutdown: false }
+    }
+
+    ///`d` resolved against its imports, built once per version of the open documents
+    fn view(&self, d: &Doc) -> Rc<View> {
+        if let Some(v) = self.views.borrow().get(&d.uri) {
+            return v.clone();
+        }
+        let v = Rc::new(self.build_view(d));
+        self.views.borrow_mut().insert(d.uri.clone(), v.clone());
+        v
+    }
+
+    ///resolve and check `d` against the modules it imports
+    fn build_view(&self, d: &Doc) -> View {
+        let mut asts = vec![d.parser.get_ast().clone()];
+        let mut sources = vec![(d.uri.clone(), d.text.clone())];
+        let mut graph = ModGraph { names: vec![String::new()], edges: vec![Vec::new()] };
+        if let Some(path) = uri_to_path(&d.uri) {
+            let layout = layout_for(&path);
+            for (n, node) in d.parser.get_ast().iter().enumerate() {
+                let NodeT::ImportNode(imp) = node else { continue };
+                let (name, file) = layout.import(&path, &imp.path);
+                let uri = path_to_uri(&file);
+                let (ast, text) = match self.docs.get(&uri) {
+                    Some(open) => (open.parser.get_ast().clone(), open.text.clone()),
+                    None => match fs::read_to_string(&file) {
+                        Ok(text) => (crate::parse(text.clone()).get_ast().clone(), text),
+                        Err(_) => continue,
+                    },
+                };
+                graph.edges[0].push((asts.len(), n));
+                graph.names.push(name);
+                graph.edges.push(Vec::new());
+                asts.push(ast);
+                sources.push((uri, text));
+            }
+        }
+        let resolver = Resolver::from_asts(&graph, &asts);
+        let (binds, mut errs) = sema::resolve(&resolver, 0, &asts[0], &[]);
+        let refs: Vec<&[NodeT]> = asts.iter().map(Vec::as_slice).collect();
+        errs.extend(sema::check(&refs, 0, &binds, &[]));
+        View { asts, sources, binds, errs }
+    }
+
+    fn diagnostics(&self, d: &Doc) -> Json {
+        let view = self.view(d);
+        let diags = d.parser.errs.iter().chain(&view.errs).map(|e| {
+            let (desc, help) = describe(&e.et);
+            let help = e.help.as_deref().unwrap_or(help);
+            let message = if help.is_empty() { desc.to_string() } else { format!("{}\nhelp: {}", desc, help) };
+            Json::obj([
+                ("range", range(&d.text, e.start, e.end)),
+                ("severity", Json::Num(if e.et.is_warning() { 2.0 } else { 1.0 })),
+                ("source", Json::str("sip")),
+                ("message", Json::str(message)),
+            ])
+        }).collect();
+        notification("textDocument/publishDiagnostics", Json::obj([
+            ("uri", Json::str(d.uri.clone())),
+            ("diagnostics", Json::Arr(diags)),
+        ]))
+    }
+
+    ///the declaration the use at `start` in `d` is bound to
+    fn lookup(&self, d: &Doc, start: usize) -> Option<Found> {
+        let view = self.view(d);
+        let found = |m: usize, detail: String, start: usize, end: usize| {
+            let (uri, text) = view.sources[m].clone();
+            Found { uri, text, detail, start, end }
+        };
+        match *view.binds.uses.get(&start)? {
+            Decl::Fn(r) => match view.asts.get(r.module)?.get(r.node)? {
+                NodeT::FUNCNode(f) => Some(found(r.module, f.signature(), f.start, f.end)),
+                _ => None,
+            },
+            Decl::Local(at) => {
+                let (detail, var) = decl::local(&view.asts[0], at)?;
+                Some(found(0, detail, var.start, var.end))
+            }
+            Decl::Const(n) | Decl::Global(n) => match &view.asts[0][n] {
+                node @ (NodeT::ConstNode(s) | NodeT::GlobalNode(s)) => Some(found(0, node.to_string(), s.start, s.end)),
+                _ => None,
+            },
+            Decl::Extern => None,
+        }
+    }
+
+    ///the declaration of the identifier under the cursor of a `TextDocumentPositionParams`
+    fn at_cursor(&self, params: &Json) -> Option<(Found, usize, usize)> {
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        let d = self.docs.get(uri)?;
+        let off = offset(&d.text, params.get("position")?)?;
+        let tok = d.ident_at(off)?;
+        let found = self.lookup(d, d.path_start(tok))?;
+        Some((found, tok.start, tok.end))
+    }
+
+    fn hover(&self, params: &Json) -> Json {
+        let Some((found, start, end)) = self.at_cursor(params) else { return Json::Null };
+        let uri = params.get("textDocument").and_then(|t| t.get("uri")).and_then(Json::as_str).unwrap_or_default();
+        let text = self.docs.get(uri).map(|d| d.text.as_str()).unwrap_or_default();
+        Json::obj([
+            ("contents", Json::obj([
+                ("kind", Json::str("markdown")),
+                ("value", Json::str(format!("```sip\n{}\n```", found.detail))),
+            ])),
+            ("range", range(text, start, end)),
+        ])
+    }
+
+    fn definition(&self, params: &Json) -> Json {
+        let Some((found, _, _)) = self.at_cursor(params) else { return Json::Null };
+        Json::obj([
+            ("uri", Json::str(found.uri)),
+            ("range", range(&found.text, found.start, found.end)),
+        ])
+    }
+
+    fn symbols(&self, params: &Json) -> Json {
+        let Some(d) = params.get("textDocument").and_then(|t| t.get("uri")).and_then(Json::as_str).and_then(|u| self.docs.get(u)) else {
+            return Json::Arr(Vec::new());
+        };
+        Json::Arr(d.funcs().map(|f| Json::obj([
+            ("name", Json::str(f.name.clone())),
+            ("detail", Json::str(f.signature())),
+            ("kind", Json::Num(SYMBOL_FUNCTION)),
+            ("range", range(&d.text, f.start, f.end)),
+            ("selectionRange", range(&d.text, f.start, f.end)),
+        ])).collect())
+    }
+
+    fn completion(&self, params: &Json) -> Json {
+        let item = |label: &str, kind: f64, detail: Option<String>| {
+            let mut pairs = vec![("label", Json::str(label)), ("kind", Json::Num(kind))];
+            if let Some(d) = detail {
+                pairs.push(("detail", Json::str(d)));
+            }
+            Json::obj(pairs)
+        };
+        let mut items: Vec<Json> = KEYWORDS.iter().map(|k| item(k, COMPLETION_KEYWORD, None))
+            .chain(TYPE_NAMES.iter().map(|t| item(t, COMPLETION_TYPE, None)))
+            .collect();
+        if let Some(d) = params.get("textDocument").and_then(|t| t.get("uri")).and_then(Json::as_str).and_then(|u| self.docs.get(u)) {
+            items.extend(d.funcs().map(|f| item(&f.name, COMPLETION_FUNCTION, Some(f.signature()))));
+        }
+        Json::Arr(items)
+    }
+
+    ///handle one message, returning what to send back
+    fn handle(&mut self, msg: &Json) -> Vec<Json> {
+        let method = msg.get("method").and_then(Json::as_str).unwrap_or_default();
+        let params = msg.get("params").cloned().unwrap_or(Json::Null);
+        let id = msg.get("id").cloned();
+        let text_of = |p: &Json| p.get("textDocument").and_then(|t| t.get("uri")).and_then(Json::as_str).map(str::to_string);
+
+        let result = match method {
+            "initialize" => Json::obj([
+                ("capabilities", Json::obj([
+                    // full document sync
+                    ("textDocumentSync", Json::Num(1.0)),
+                    ("hoverProvider", Json::Bool(true)),
+                    ("definitionProvider", Json::Bool(true)),
+                    ("documentSymbolProvider", Json::Bool(true)),
+                    ("completionProvider", Json::obj::<&str>([])),
+                ])),
+                ("serverInfo", Json::obj([
+                    ("name", Json::str("sip")),
+                    ("version", Json::str(env!("CARGO_PKG_VERSION"))),
+                ])),
+            ]),
+            "shutdown" => {
+                self.shutdown = true;
+                Json::Null
+            }
+            "textDocument/didOpen" | "textDocument/didChange" => {
+                let text = if method == "textDocument/didOpen" {
+                    params.get("textDocument").and_then(|t| t.get("text")).and_then(Json::as_str)
+                } else {
+                    params.get("contentChanges").and_then(Json::as_arr).and_then(|c| c.last())
+                        .and_then(|c| c.get("text")).and_then(Json::as_str)
+                };
+                let (Some(uri), Some(text)) = (text_of(&params), text) else { return Vec::new() };
+                self.views.get_mut().clear();
+                let d = Doc::new(uri.clone(), text.to_string());
+                let out = vec![self.diagnostics(&d)];
+                self.docs.insert(uri, d);
+                return out;
+            }
+            "textDocument/didClose" => {
+                let Some(uri) = text_of(&params) else { return Vec::new() };
+                self.views.get_mut().clear();
+                self.docs.remove(&uri);
+                return vec![notification("textDocument/publishDiagnostics", Json::obj([
+                    ("uri", Json::str(uri)),
+                    ("diagnostics", Json::Arr(Vec::new())),
+                ]))];
+            }
+            "textDocument/hover" => self.hover(&params),
+            "textDocument/definition" => self.definition(&params),
+            "textDocument/documentSymbol" => self.symbols(&params),
+            "textDocument/completion" => self.completion(&params),
+            _ => {
+                return match id {
+                    Some(id) => vec![error(id, METHOD_NOT_FOUND, &format!("unsupported method '{}'", method))],
+                    // notifications we do not care about
+                    None => Vec::new(),
+                };
+            }
+        };
+        id.map(|id| response(id, result)).into_iter().collect()
+    }
+}
+
+///`sip lsp`: serve the language server protocol over stdin/stdout until `exit`
+pub fn serve() -> Exit {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    serve_on(&mut input, &mut io::stdout().lock())
+}
+
+pub fn serve_on(input: &mut impl BufRead, out: &mut impl Write) -> Exit {
+    let mut server = Server::new();
+    loop {
+        let msg = match read_msg(input) {
+            Ok(Some(Ok(m))) => m,
+            Ok(Some(Err(e))) => {
+                // the id is unknown, the protocol answers a parse error with a null one
+                if let Err(e) = write_msg(out, &error(Json::Null, PARSE_ERROR, &format!("invalid JSON: {}", e))) {
+                    eprintln!("error writing LSP message!\nerr => {}", e);
+                    return Exit::Io;
+                }
+                continue;
+            }
+            // the client went away without `exit`
+            Ok(None) => return Exit::Io,
+            Err(e) => {
+                eprintln!("error reading LSP message!\nerr => {}", e);
+                return Exit::Io;
+            }
+        };
+        if msg.get("method").and_then(Json::as_str) == Some("exit") {
+            // the protocol asks for exit code 1 when `shutdown` was skipped
+            return if server.shutdown { Exit::Ok } else { Exit::Compile };
+        }
+        for reply in server.handle(&msg) {
+            if let Err(e) = write_msg(out, &reply) {
+                eprintln!("error writing LSP message!\nerr => {}", e);
+                return Exit::Io;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str = "file:///nowhere/main.sip";
+
+    fn open(text: &str) -> Server {
+        let mut server = Server::new();
+        server.handle(&notification("textDocument/didOpen", Json::obj([
+            ("textDocument", Json::obj([("uri", Json::str(URI)), ("text", Json::str(text))])),
+        ])));
+        server
+    }
+
+    ///hover text and definition line of the `nth` occurrence of the whole word `word`
+    fn at(server: &Server, word: &str, nth: usize) -> Option<(String, usize)> {
+        let text = &server.docs[URI].text;
+        let is_word = |i: usize| text[..i].chars().next_back().is_none_or(|c| !c.is_alphanumeric())
+            && text[i + word.len()..].chars().next().is_none_or(|c| !c.is_alphanumeric());
+        let off = text.match_indices(word).map(|(i, _)| i).filter(|&i| is_word(i)).nth(nth)?;
+        let pos = doc::position(text, off);
+        let params = Json::obj([("textDocument", Json::obj([("uri", Json::str(URI))])), ("position", pos)]);
+        let hover = server.hover(&params);
+        let detail = hover.get("contents")?.get("value")?.as_str()?.trim_matches(|c| c == '`' || c == '\n').trim_start_matches("sip\n");
+        let line = server.definition(&params).get("range")?.get("start")?.get("line")?.as_usize()?;
+        Some((detail.to_string(), line))
+    }
+
+    #[test]
+    fn empty_body_is_a_diagnostic() {
+        let mut server = Server::new();
+        let out = server.handle(&notification("textDocument/didOpen", Json::obj([
+            ("textDocument", Json::obj([("uri", Json::str(URI)), ("text", Json::str("fn f() @ i32 {}"))])),
+        ])));
+        let diags = out[0].get("params").and_then(|p| p.get("diagnostics")).and_then(Json::as_arr).unwrap();
+        assert!(!diags.is_empty());
+    }
+
+    #[test]
+    fn every_prefix_of_a_program_is_handled() {
+        // what the server sees while a file is typed in, none of it may end the process
+        let src = "import util;
+// a comment with \"quotes\" in it
+const K: i32 = 2;
+pub fn f(i32 x, u8 y) @ i32 {
+    let mut n: i32 = x * K; // trailing
+    while n > 0 { n -= 1; }
+    if y as i32 > n { ret 1; }
+    loop { break; }
+    util.g(n, \"name\", 'c')
+}
+";
+        for end in 0..=src.len() {
+            let d = Doc::new(URI.into(), src[..end].to_string());
+            let server = Server::new();
+            server.diagnostics(&d);
+        }
+    }
+
+    #[test]
+    fn lookup_follows_bindings() {
+        let s = open("const K: i32 = 3;\nfn f(i32 x) @ i32 { x }\nfn g(i32 x) @ i32 {\n    let mut f: i32 = x + K;\n    f + 1\n}\n");
+        assert_eq!(at(&s, "K", 1), Some(("const K: i32 = 3".into(), 0)));
+        // the param of `g`, not the one of `f` with the same name
+        assert_eq!(at(&s, "x", 3), Some(("i32 x".into(), 2)));
+        // the local shadows the fn
+        assert_eq!(at(&s, "f", 2), Some(("let mut f: i32".into(), 3)));
+        assert_eq!(at(&s, "i32", 0), None);
+    }
+
+    #[test]
+    fn lookup_calls() {
+        let s = open("fn f(i32 x) @ i32 { x }\nfn g() @ i32 { f(1) }\n");
+        assert_eq!(at(&s, "f", 1), Some(("fn f(i32 x) @ i32".into(), 0)));
+    }
+
+    #[test]
+    fn malformed_body_gets_a_parse_error() {
+        let frame = |body: &str| format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let input = [
+            frame("{\"id\": 1, \"method\": "),
+            frame(r#"{"jsonrpc": "2.0", "id": 2, "method": "shutdown"}"#),
+            frame(r#"{"jsonrpc": "2.0", "method": "exit"}"#),
+        ].concat();
+        let mut out = Vec::new();
+        assert!(matches!(serve_on(&mut input.as_bytes(), &mut out), Exit::Ok));
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("-32700"), "{}", out);
+        assert!(out.contains(r#""id":2"#), "{}", out);
+    }
+
+    #[test]
+    fn view_is_reused_until_a_document_changes() {
+        let mut s = open("fn f() @ i32 { 1 }\n");
+        let first = s.view(&s.docs[URI]);
+        assert!(Rc::ptr_eq(&first, &s.view(&s.docs[URI])));
+        s.handle(&notification("textDocument/didChange", Json::obj([
+            ("textDocument", Json::obj([("uri", Json::str(URI))])),
+            ("contentChanges", Json::Arr(vec![Json::obj([("text", Json::str("fn f() @ i32 { 2 }\n"))])])),
+        ])));
+        assert!(!Rc::ptr_eq(&first, &s.view(&s.docs[URI])));
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::{self, BufRead, Write};
+
+use crate::json::Json;
+
+///read one `Content-Length` framed message, `None` at end of input.
+///a body that is not JSON is `Some(Err(..))`, the framing is intact so the next message can still be read
+pub fn read_msg(input: &mut impl BufRead) -> io::Result<Option<Result<Json, String>>> {
+    let mut len = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = line.split_once(':') && k.eq_ignore_ascii_case("content-length") {
+            len = v.trim().parse::<usize>().ok();
+        }
+    }
+    let len = len.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; len];
+    input.read_exact(&mut body)?;
+    let parsed = String::from_utf8(body).map_err(|e| e.to_string()).and_then(|text| Json::parse(&text).map_err(|e| e.to_string()));
+    Ok(Some(parsed))
+}
+
+pub fn write_msg(out: &mut impl Write, msg: &Json) -> io::Result<()> {
+    let body = msg.to_string();
+    write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    out.flush()
+}
+
+pub fn response(id: Json, result: Json) -> Json {
+    Json::obj([("jsonrpc", Json::str("2.0")), ("id", id), ("result", result)])
+}
+
+pub fn error(id: Json, code: i32, msg: &str) -> Json {
+    Json::obj([
+        ("jsonrpc", Json::str("2.0")),
+        ("id", id),
+        ("error", Json::obj([("code", Json::Num(code as f64)), ("message", Json::str(msg))])),
+    ])
+}
+
+pub fn notification(method: &str, params: Json) -> Json {
+    Json::obj([("jsonrpc", Json::str("2.0")), ("method", Json::str(method)), ("params", params)])
+}
@@ -1,87 +1,92 @@
 use std::env;
-use std::fs::File;
-use std::process::exit;
-use std::time::Instant;
+use std::path::Path;
 
-use memmap2::Mmap;
-use help::help;
-use parser::Parser;
-use token::lexer::lex;
-
-pub mod help;
-pub mod token;
-pub mod ast;
-pub mod parser;
-pub mod err;
+use sip::{
+    cli::{self, defs::{Cmd, Emit, Exit, Opts}},
+    driver, fmt,
+    help::{help, help_cmd, version},
+    lsp, manifest, term, watch,
+};
 
 fn main() {
-    let mut args = env::args();
-    let _bin = args.next();
-    let command = args.next().unwrap_or_else(|| {
-        eprintln!("Error: Missing command.\n");
-        help();
-        exit(1);
-    });
-    let filepath = args.next().unwrap_or_else(|| {
-        eprintln!("Error: Missing file path.\n");
-        help();
-        exit(1);
-    });
-
-    if command == "help" {
-        help();
-        return;
-    }
-
-    match command.as_str() {
-        "build" => {
-            // Record the time taken to open the file
-            let start_open = Instant::now();
-            let file = match File::open(&filepath) {
-                Ok(f) => f,
-                Err(e) => {
-                    eprintln!("error opening file!\nerr => {:?}", e);
-                    exit(1);
-                }
-            };
-            let open_duration = start_open.elapsed();
-
-            // Record the time taken to memory-map the file
-            let start_map = Instant::now();
-            let mmap = unsafe {
-                match Mmap::map(&file) {
-                    Ok(m) => m,
-                    Err(e) => {
-                        eprintln!("error memory-mapping file!\nerr => {:?}", e);
-                        exit(1);
-                    }
-                }
-            };
-            let map_duration = start_map.elapsed();
+    // a panic is a bug in the compiler, not in the user's code
+    std::panic::set_hook(Box::new(|info| {
+        eprintln!("internal compiler error: {}", info);
+        Exit::Internal.exit();
+    }));
 
-            // Record the time taken to lex the file contents
-            let start_lex = Instant::now();
-            let tokens = lex(&mmap[..]); // pass raw &[u8] directly
-            let lex_duration = start_lex.elapsed();
+    let opts = match cli::parse_args(env::args().skip(1)) {
+        Ok(o) => o,
+        Err(e) => {
+            term::init(term::ColorMode::Auto, false);
+            eprintln!("Error: {}.\n", e);
+            help();
+            Exit::Usage.exit();
+        }
+    };
+    term::init(opts.color, opts.ascii);
 
-            // Print the times in a nice format
-            println!("File opened in: {:.2?}", open_duration);
-            println!("File memory-mapped in: {:.2?}", map_duration);
-            println!("File tokenized in: {:.2?}", lex_duration);
-            //println!("{:?}", tokens);
-            //println!("tokens:\n{:#?}",tokens);
-            let mut parser = Parser::new(tokens);
-            parser.parse();
-            if parser.erroccur(){
-                parser.show_errs(&mmap);
+    let code = match &opts.cmd {
+        Cmd::Help(None) => {
+            help();
+            Exit::Ok
+        }
+        Cmd::Help(Some(cmd)) => {
+            if help_cmd(cmd) {
+                Exit::Ok
+            } else {
+                eprintln!("Error: unknown command '{}'.\n", cmd);
+                help();
+                Exit::Usage
             }
-            // Optionally, print the tokens for debugging
         }
+        Cmd::Version => {
+            version();
+            Exit::Ok
+        }
+        Cmd::Build => build(&opts),
+        Cmd::Run => match driver::build::setup(&opts) {
+            Ok(setup) => driver::run::run(&setup),
+            Err(code) => code,
+        },
+        Cmd::New => new(&opts),
+        Cmd::Watch => watch::watch(&opts),
+        Cmd::Lsp => lsp::serve(),
+        Cmd::Fmt => fmt::fmt(&opts),
+    };
+    code.exit();
+}
 
-        _ => {
-            eprintln!("Unknown command: '{}'\n", command);
-            help();
-            exit(1);
+fn new(opts: &Opts) -> Exit {
+    let Some(name) = &opts.path else {
+        eprintln!("Error: Missing project name.\n");
+        help_cmd("new");
+        return Exit::Usage;
+    };
+    let dir = Path::new(name);
+    let pkg = dir.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    if !manifest::valid_name(pkg) {
+        eprintln!("Error: invalid project name '{}' (use letters, digits and '_', not starting with a digit).", pkg);
+        return Exit::Usage;
+    }
+    match manifest::scaffold::new_project(dir, pkg) {
+        Ok(()) => {
+            if !opts.quiet {
+                println!("Created project '{}' in {}", pkg, dir.display());
+            }
+            Exit::Ok
         }
+        Err(e) => {
+            eprintln!("error creating project!\nerr => {}", e);
+            Exit::Io
+        }
+    }
+}
+
+fn build(opts: &Opts) -> Exit {
+    match driver::build::setup(opts) {
+        Ok(setup) if setup.opts.emit == Emit::Check => driver::build::check(&setup),
+        Ok(setup) => driver::emit::emit(&setup),
+        Err(code) => code,
     }
 }
@@ -0,0 +1,124 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use super::{Manifest, ManifestErr};
+use crate::module::layout::{Layout, PkgRoot};
+
+///a package reached from the root manifest
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub manifest: Manifest,
+    ///canonical directory of the manifest
+    pub dir: PathBuf,
+    ///indexes of the direct dependencies
+    pub deps: Vec<usize>,
+}
+
+///every package of a build, `pkgs[0]` is the root
+#[derive(Debug, Clone)]
+pub struct DepGraph {
+    pub pkgs: Vec<Package>,
+}
+
+#[derive(Debug)]
+pub enum DepErr {
+    Manifest(ManifestErr),
+    ///package names along the cycle, the first one repeated at the end
+    Cycle(Vec<String>),
+    ///one package name found in two places
+    Conflict { name: String, a: PathBuf, b: PathBuf },
+    ///`version = "..."` in a dependency does not match the package
+    Version { name: String, wanted: String, found: String },
+    ///the key in `[dependencies]` differs from the package name
+    Name { wanted: String, found: String, dir: PathBuf },
+}
+
+impl fmt::Display for DepErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepErr::Manifest(e) => write!(f, "{}", e),
+            DepErr::Cycle(chain) => write!(f, "dependency cycle: {}", chain.join(" -> ")),
+            DepErr::Conflict { name, a, b } => write!(f, "package '{}' is found both in {} and in {}", name, a.display(), b.display()),
+            DepErr::Version { name, wanted, found } => write!(f, "package '{}' is required at version {} but is version {}", name, wanted, found),
+            DepErr::Name { wanted, found, dir } => write!(f, "dependency '{}' points at {}, which is package '{}'", wanted, dir.display(), found),
+        }
+    }
+}
+
+impl From<ManifestErr> for DepErr {
+    fn from(e: ManifestErr) -> Self {
+        DepErr::Manifest(e)
+    }
+}
+
+fn canonical(dir: &Path) -> Result<PathBuf, DepErr> {
+    dir.canonicalize().map_err(|e| DepErr::Manifest(ManifestErr::Io(dir.to_path_buf(), e)))
+}
+
+///load every manifest reachable from `root` through path dependencies
+pub fn resolve(root: Manifest) -> Result<DepGraph, DepErr> {
+    let mut g = Walk { pkgs: Vec::new(), by_name: HashMap::new(), chain: Vec::new() };
+    g.visit(root)?;
+    Ok(DepGraph { pkgs: g.pkgs })
+}
+
+struct Walk {
+    pkgs: Vec<Package>,
+    by_name: HashMap<String, usize>,
+    ///names of the packages currently being visited
+    chain: Vec<String>,
+}
+
+impl Walk {
+    fn visit(&mut self, m: Manifest) -> Result<usize, DepErr> {
+        let dir = canonical(&m.dir)?;
+        if let Some(from) = self.chain.iter().position(|n| *n == m.name) {
+            let mut cycle = self.chain[from..].to_vec();
+            cycle.push(m.name);
+            return Err(DepErr::Cycle(cycle));
+        }
+        if let Some(&i) = self.by_name.get(&m.name) {
+            if self.pkgs[i].dir != dir {
+                return Err(DepErr::Conflict { name: m.name, a: self.pkgs[i].dir.clone(), b: dir });
+            }
+            return Ok(i);
+        }
+
+        let i = self.pkgs.len();
+        self.by_name.insert(m.name.clone(), i);
+        self.chain.push(m.name.clone());
+        self.pkgs.push(Package { manifest: m.clone(), dir: dir.clone(), deps: Vec::new() });
+        for dep in &m.deps {
+            let dm = Manifest::load(&dir.join(&dep.path))?;
+            if dm.name != dep.name {
+                return Err(DepErr::Name { wanted: dep.name.clone(), found: dm.name, dir: dm.dir });
+            }
+            if let Some(wanted) = &dep.version && *wanted != dm.version {
+                return Err(DepErr::Version { name: dep.name.clone(), wanted: wanted.clone(), found: dm.version });
+            }
+            let j = self.visit(dm)?;
+            self.pkgs[i].deps.push(j);
+        }
+        self.chain.pop();
+        Ok(i)
+    }
+}
+
+impl DepGraph {
+    ///module layout of the build: the root package plus every dependency, prefixed by name
+    pub fn layout(&self) -> Layout {
+        let pkgs = self.pkgs.iter().enumerate().map(|(i, p)| {
+            let entry = p.dir.join(&p.manifest.entry);
+            PkgRoot {
+                name: (i != 0).then(|| p.manifest.name.clone()),
+                dir: entry.parent().map(Path::to_path_buf).unwrap_or_else(|| p.dir.clone()),
+                entry: Some(entry),
+                deps: p.deps.iter().map(|&d| self.pkgs[d].manifest.name.clone()).collect(),
+            }
+        }).collect();
+        Layout { pkgs }
+    }
+}
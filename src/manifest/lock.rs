@@ -0,0 +1,59 @@
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use super::deps::DepGraph;
+
+///file name of the lockfile, next to `sip.toml`
+pub const LOCKFILE: &str = "sip.lock";
+
+///`to` relative to `from`, both absolute
+fn relative(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut out = PathBuf::new();
+    for _ in common..from.len() {
+        out.push("..");
+    }
+    for c in &to[common..] {
+        out.push(c);
+    }
+    if out.as_os_str().is_empty() {
+        out.push(".");
+    }
+    out
+}
+
+///the lockfile contents, one table per package sorted by name
+pub fn render(g: &DepGraph) -> String {
+    let root = &g.pkgs[0].dir;
+    let mut pkgs: Vec<_> = g.pkgs.iter().collect();
+    pkgs.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+
+    let mut out = String::from("# generated by sip, do not edit by hand\n");
+    for p in pkgs {
+        let mut deps: Vec<String> = p.deps.iter().map(|&d| format!("\"{}\"", g.pkgs[d].manifest.name)).collect();
+        deps.sort_unstable();
+        out.push_str(&format!(
+            "\n[{}]\nversion = \"{}\"\npath = \"{}\"\ndependencies = [{}]\n",
+            p.manifest.name,
+            p.manifest.version,
+            relative(root, &p.dir).display().to_string().replace('\\', "/"),
+            deps.join(", "),
+        ));
+    }
+    out
+}
+
+///write `sip.lock` next to the root manifest, `true` if it changed
+pub fn write(g: &DepGraph) -> io::Result<bool> {
+    let file = g.pkgs[0].dir.join(LOCKFILE);
+    let text = render(g);
+    if fs::read_to_string(&file).is_ok_and(|old| old == text) {
+        return Ok(false);
+    }
+    fs::write(&file, text)?;
+    Ok(true)
+}
@@ -0,0 +1,220 @@
+pub mod deps;
+pub mod lock;
+pub mod scaffold;
+pub mod toml;
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use toml::Value;
+
+///file name of the project manifest
+pub const MANIFEST: &str = "sip.toml";
+
+///a local path dependency: `util = { path = "../util" }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dep {
+    pub name: String,
+    ///as written, relative to the manifest's directory
+    pub path: PathBuf,
+    ///exact version the package must have, if given
+    pub version: Option<String>,
+}
+
+///a parsed `sip.toml`
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    ///directory holding the manifest, the project root
+    pub dir: PathBuf,
+    pub name: String,
+    pub version: String,
+    ///entry file relative to `dir`
+    pub entry: PathBuf,
+    pub target: Option<String>,
+    pub opt: Option<u8>,
+    pub deps: Vec<Dep>,
+}
+
+#[derive(Debug)]
+pub enum ManifestErr {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::TomlErr),
+    ///well formed but missing or wrong fields
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for ManifestErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestErr::Io(p, e) => write!(f, "{}: {}", p.display(), e),
+            ManifestErr::Parse(p, e) => write!(f, "{}:{}: {}", p.display(), e.line, e.msg),
+            ManifestErr::Invalid(p, m) => write!(f, "{}: {}", p.display(), m),
+        }
+    }
+}
+
+///`name` is usable as a package and module name
+pub fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+///closest directory at or above `start` holding a `sip.toml`
+pub fn find(start: &Path) -> Option<PathBuf> {
+    let start = start.canonicalize().ok()?;
+    start.ancestors().find(|d| d.join(MANIFEST).is_file()).map(Path::to_path_buf)
+}
+
+impl Manifest {
+    ///read `dir/sip.toml`
+    pub fn load(dir: &Path) -> Result<Self, ManifestErr> {
+        let file = dir.join(MANIFEST);
+        let src = fs::read_to_string(&file).map_err(|e| ManifestErr::Io(file.clone(), e))?;
+        let doc = toml::parse(&src).map_err(|e| ManifestErr::Parse(file.clone(), e))?;
+        let invalid = |m: String| ManifestErr::Invalid(file.clone(), m);
+        let table = |name: &str| doc.iter().find(|(t, _)| t == name).map(|(_, kv)| Value::Table(kv.clone()));
+
+        let pkg = table("package").ok_or_else(|| invalid("missing [package] table".into()))?;
+        let string = |key: &str| -> Result<Option<String>, ManifestErr> {
+            match pkg.get(key) {
+                None => Ok(None),
+                Some(v) => v.as_str().map(|s| Some(s.to_string()))
+                    .ok_or_else(|| invalid(format!("`package.{}` must be a string", key))),
+            }
+        };
+
+        let name = string("name")?.ok_or_else(|| invalid("missing `package.name`".into()))?;
+        if !valid_name(&name) {
+            return Err(invalid(format!("invalid package name '{}'", name)));
+        }
+        let version = string("version")?.unwrap_or_else(|| "0.1.0".into());
+        let entry = PathBuf::from(string("entry")?.unwrap_or_else(|| "src/main.sip".into()));
+        let target = string("target")?;
+        let opt = match pkg.get("opt-level") {
+            None => None,
+            Some(v) => match v.as_int() {
+                Some(n @ 0..=3) => Some(n as u8),
+                _ => return Err(invalid("`package.opt-level` must be an integer from 0 to 3".into())),
+            },
+        };
+
+        let mut deps = Vec::new();
+        if let Some(Value::Table(kv)) = table("dependencies") {
+            for (dep, spec) in kv {
+                let path = spec.get("path").and_then(Value::as_str)
+                    .ok_or_else(|| invalid(format!("dependency '{}' needs a `path`, only local dependencies are supported", dep)))?;
+                let version = match spec.get("version") {
+                    None => None,
+                    Some(v) => Some(v.as_str().ok_or_else(|| invalid(format!("version of dependency '{}' must be a string", dep)))?.to_string()),
+                };
+                deps.push(Dep { name: dep, path: PathBuf::from(path), version });
+            }
+        }
+
+        Ok(Self { dir: dir.to_path_buf(), name, version, entry, target, opt, deps })
+    }
+
+    ///entry file as a path usable from the current directory
+    pub fn entry_path(&self) -> PathBuf {
+        self.dir.join(&self.entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deps::{DepErr, resolve}, lock, *};
+
+    ///a fresh directory for one test
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sip-manifest-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    ///write `<root>/<name>/sip.toml` with `deps` as the `[dependencies]` lines
+    fn pkg(root: &Path, name: &str, version: &str, deps: &[&str]) -> PathBuf {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        let text = format!("[package]\nname = \"{}\"\nversion = \"{}\"\n\n[dependencies]\n{}\n", name, version, deps.join("\n"));
+        fs::write(dir.join(MANIFEST), text).unwrap();
+        dir
+    }
+
+    fn resolve_at(dir: &Path) -> Result<deps::DepGraph, DepErr> {
+        resolve(Manifest::load(dir)?)
+    }
+
+    #[test]
+    fn toml_subset() {
+        let doc = toml::parse("top = 1 # comment\n[package]\nname = \"a\\tb\"\nflag = true\nn = -1_000\ndep = { path = \"../x\", version = \"1\" }\n").unwrap();
+        assert_eq!(doc[0], ("".into(), vec![("top".into(), toml::Value::Int(1))]));
+        let pkg = toml::Value::Table(doc[1].1.clone());
+        assert_eq!(pkg.get("name").and_then(toml::Value::as_str), Some("a\tb"));
+        assert_eq!(pkg.get("flag"), Some(&toml::Value::Bool(true)));
+        assert_eq!(pkg.get("n").and_then(toml::Value::as_int), Some(-1000));
+        assert_eq!(pkg.get("dep").and_then(|d| d.get("path")).and_then(toml::Value::as_str), Some("../x"));
+
+        let err = |src: &str| toml::parse(src).unwrap_err();
+        assert_eq!(err("[a]\n[a]").line, 2);
+        assert!(err("a = 1\na = 2").msg.contains("twice"));
+        assert!(err("a = \"open").msg.contains("unterminated"));
+        assert!(err("a = 1 2").msg.contains("end of the line"));
+        assert!(err("a = { b = 1").msg.contains("inline table"));
+    }
+
+    #[test]
+    fn dependency_cycle() {
+        let root = scratch("cycle");
+        let a = pkg(&root, "a", "0.1.0", &["b = { path = \"../b\" }"]);
+        pkg(&root, "b", "0.1.0", &["c = { path = \"../c\" }"]);
+        pkg(&root, "c", "0.1.0", &["a = { path = \"../a\" }"]);
+        match resolve_at(&a) {
+            Err(DepErr::Cycle(chain)) => assert_eq!(chain, ["a", "b", "c", "a"]),
+            other => panic!("{:?}", other.map(|g| g.pkgs.len())),
+        }
+    }
+
+    #[test]
+    fn same_name_in_two_places() {
+        let root = scratch("conflict");
+        let app = pkg(&root, "app", "0.1.0", &["util = { path = \"../util\" }", "other = { path = \"../other\" }"]);
+        pkg(&root, "util", "0.1.0", &[]);
+        pkg(&root, "other", "0.1.0", &["util = { path = \"../copy/util\" }"]);
+        pkg(&root.join("copy"), "util", "0.1.0", &[]);
+        assert!(matches!(resolve_at(&app), Err(DepErr::Conflict { name, .. }) if name == "util"));
+    }
+
+    #[test]
+    fn version_and_name_checks() {
+        let root = scratch("version");
+        let app = pkg(&root, "app", "0.1.0", &["util = { path = \"../util\", version = \"2.0.0\" }"]);
+        pkg(&root, "util", "1.0.0", &[]);
+        assert!(matches!(resolve_at(&app), Err(DepErr::Version { wanted, found, .. }) if wanted == "2.0.0" && found == "1.0.0"));
+
+        let app = pkg(&root, "app", "0.1.0", &["tools = { path = \"../util\" }"]);
+        assert!(matches!(resolve_at(&app), Err(DepErr::Name { wanted, found, .. }) if wanted == "tools" && found == "util"));
+
+        pkg(&root, "app", "0.1.0", &["util = { path = \"../util\", version = \"1.0.0\" }"]);
+        assert_eq!(resolve_at(&app).unwrap().pkgs.len(), 2);
+    }
+
+    #[test]
+    fn lockfile_is_sorted_and_stable() {
+        let root = scratch("lock");
+        let app = pkg(&root, "app", "0.1.0", &["zed = { path = \"../zed\" }", "util = { path = \"../util\" }"]);
+        pkg(&root, "zed", "0.2.0", &["util = { path = \"../util\" }"]);
+        pkg(&root, "util", "1.0.0", &[]);
+        let g = resolve_at(&app).unwrap();
+        assert_eq!(lock::render(&g), "# generated by sip, do not edit by hand\n\
+            \n[app]\nversion = \"0.1.0\"\npath = \".\"\ndependencies = [\"util\", \"zed\"]\n\
+            \n[util]\nversion = \"1.0.0\"\npath = \"../util\"\ndependencies = []\n\
+            \n[zed]\nversion = \"0.2.0\"\npath = \"../zed\"\ndependencies = [\"util\"]\n");
+        assert!(lock::write(&g).unwrap());
+        // nothing changed, so the file is left alone
+        assert!(!lock::write(&resolve_at(&app).unwrap()).unwrap());
+    }
+}
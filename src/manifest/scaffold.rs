@@ -0,0 +1,24 @@
+use std::{fs, io, path::Path};
+
+use super::MANIFEST;
+
+///create a new project named `name` in the directory `dir`, which must not exist yet
+pub fn new_project(dir: &Path, name: &str) -> io::Result<()> {
+    if dir.exists() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("'{}' already exists", dir.display())));
+    }
+    fs::create_dir_all(dir.join("src"))?;
+    fs::write(dir.join(MANIFEST), format!(
+r#"[package]
+name = "{}"
+version = "0.1.0"
+entry = "src/main.sip"
+target = "native"
+opt-level = 0
+
+[dependencies]
+"#, name))?;
+    fs::write(dir.join("src").join("main.sip"), "fn main() @ i32 { ret 0 }\n")?;
+    fs::write(dir.join(".gitignore"), "/target\n")?;
+    Ok(())
+}
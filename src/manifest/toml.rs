@@ -0,0 +1,209 @@
+//!the small TOML subset `sip.toml` needs: tables, strings, integers,
+//!booleans and inline tables
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    ///inline table, keys keep file order
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Table(pairs) => pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+///a parse error with the 1-based line it happened on
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlErr {
+    pub line: usize,
+    pub msg: String,
+}
+
+///`[table]` sections in file order, keys before the first header go in `""`
+pub type Doc = Vec<(String, Vec<(String, Value)>)>;
+
+pub fn parse(src: &str) -> Result<Doc, TomlErr> {
+    let mut doc: Doc = vec![(String::new(), Vec::new())];
+    for (i, raw) in src.lines().enumerate() {
+        let err = |msg: &str| TomlErr { line: i + 1, msg: msg.to_string() };
+        let mut cur = Cursor { s: raw.as_bytes(), pos: 0 };
+        cur.skip_ws();
+        if cur.done() {
+            continue;
+        }
+        if cur.eat(b'[') {
+            let name = cur.key().ok_or_else(|| err("expected a table name"))?;
+            cur.skip_ws();
+            if !cur.eat(b']') {
+                return Err(err("expected ']' after the table name"));
+            }
+            if doc.iter().any(|(t, _)| *t == name) {
+                return Err(err(&format!("table [{}] is defined twice", name)));
+            }
+            doc.push((name, Vec::new()));
+        } else {
+            let (key, val) = cur.pair().map_err(|m| err(&m))?;
+            let table = &mut doc.last_mut().expect("root table").1;
+            if table.iter().any(|(k, _)| *k == key) {
+                return Err(err(&format!("key '{}' is defined twice", key)));
+            }
+            table.push((key, val));
+        }
+        cur.skip_ws();
+        if !cur.done() {
+            return Err(err("unexpected characters at the end of the line"));
+        }
+    }
+    Ok(doc)
+}
+
+struct Cursor<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.pos).copied()
+    }
+
+    ///end of line, a comment counts as the end
+    fn done(&self) -> bool {
+        matches!(self.peek(), None | Some(b'#'))
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    ///bare or quoted key
+    fn key(&mut self) -> Option<String> {
+        self.skip_ws();
+        if self.peek() == Some(b'"') {
+            return self.string().ok();
+        }
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == b'_' || c == b'-') {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| String::from_utf8_lossy(&self.s[start..self.pos]).into_owned())
+    }
+
+    fn pair(&mut self) -> Result<(String, Value), String> {
+        let key = self.key().ok_or("expected a key")?;
+        self.skip_ws();
+        if !self.eat(b'=') {
+            return Err(format!("expected '=' after '{}'", key));
+        }
+        self.skip_ws();
+        Ok((key, self.value()?))
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some(b'"') => self.string().map(Value::Str),
+            Some(b'{') => {
+                self.pos += 1;
+                let mut pairs: Vec<(String, Value)> = Vec::new();
+                self.skip_ws();
+                if self.eat(b'}') {
+                    return Ok(Value::Table(pairs));
+                }
+                loop {
+                    let (k, v) = self.pair()?;
+                    if pairs.iter().any(|(p, _)| *p == k) {
+                        return Err(format!("key '{}' is defined twice", k));
+                    }
+                    pairs.push((k, v));
+                    self.skip_ws();
+                    if self.eat(b'}') {
+                        return Ok(Value::Table(pairs));
+                    }
+                    if !self.eat(b',') {
+                        return Err("expected ',' or '}' in inline table".into());
+                    }
+                }
+            }
+            Some(b't') if self.s[self.pos..].starts_with(b"true") => {
+                self.pos += 4;
+                Ok(Value::Bool(true))
+            }
+            Some(b'f') if self.s[self.pos..].starts_with(b"false") => {
+                self.pos += 5;
+                Ok(Value::Bool(false))
+            }
+            Some(c) if c.is_ascii_digit() || c == b'-' || c == b'+' => {
+                let start = self.pos;
+                self.pos += 1;
+                while matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == b'_') {
+                    self.pos += 1;
+                }
+                let text: String = String::from_utf8_lossy(&self.s[start..self.pos]).replace('_', "");
+                text.parse().map(Value::Int).map_err(|_| format!("invalid integer '{}'", text))
+            }
+            _ => Err("expected a string, integer, boolean or inline table".into()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1; // opening quote
+        let mut out = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err("unterminated string".into()),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return String::from_utf8(out).map_err(|_| "invalid utf8 in string".into());
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = match self.peek() {
+                        Some(b'n') => b'\n',
+                        Some(b't') => b'\t',
+                        Some(b'"') => b'"',
+                        Some(b'\\') => b'\\',
+                        _ => return Err("unsupported escape in string".into()),
+                    };
+                    out.push(c);
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
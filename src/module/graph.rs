@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::defs::NodeT,
+    driver::Unit,
+    err::{def::ErrT, suggest::{did_you_mean, suggest}},
+};
+
+use super::layout::Layout;
+
+///which module imports which, built from the `import` declarations of every unit
+#[derive(Debug, Default)]
+pub struct ModGraph {
+    ///module path of every unit, same order as the units
+    pub names: Vec<String>,
+    ///per module: `(imported module, index of the import node in the ast)`
+    pub edges: Vec<Vec<(usize, usize)>>,
+}
+
+impl ModGraph {
+    ///build the graph, reporting unknown modules and import cycles on the importing unit
+    pub fn build(layout: &Layout, units: &mut [Unit]) -> Self {
+        let names: Vec<String> = units.iter().map(|u| layout.mod_path(&u.path)).collect();
+        let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+
+        let mut edges = vec![Vec::new(); units.len()];
+        for (m, unit) in units.iter_mut().enumerate() {
+            let mut missing = Vec::new();
+            for (n, node) in unit.parser.get_ast().iter().enumerate() {
+                let NodeT::ImportNode(imp) = node else { continue };
+                match index.get(layout.import(&unit.path, &imp.path).0.as_str()) {
+                    Some(&t) => edges[m].push((t, n)),
+                    None => missing.push(n),
+                }
+            }
+            for n in missing {
+                let NodeT::ImportNode(imp) = &unit.parser.get_ast()[n] else { continue };
+                let (line, start, end) = (imp.line, imp.start, imp.end);
+                let help = suggest(&imp.path.join("."), names.iter().map(String::as_str)).map(|s| did_you_mean(&s));
+                unit.parser.add_err_help(ErrT::UnknownModule, line, start, end, help);
+            }
+        }
+
+        let graph = Self { names, edges };
+        graph.cycles(units);
+        graph
+    }
+
+    ///depth-first walk reporting every back edge as a cycle
+    fn cycles(&self, units: &mut [Unit]) {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark { New, Active, Done }
+        let mut mark = vec![Mark::New; self.names.len()];
+
+        for root in 0..self.names.len() {
+            if mark[root] != Mark::New {
+                continue;
+            }
+            // (module, next edge to visit)
+            let mut stack = vec![(root, 0usize)];
+            mark[root] = Mark::Active;
+            while let Some(top) = stack.last_mut() {
+                let (m, next) = *top;
+                top.1 += 1;
+                let Some(&(t, node)) = self.edges[m].get(next) else {
+                    mark[m] = Mark::Done;
+                    stack.pop();
+                    continue;
+                };
+                match mark[t] {
+                    Mark::New => {
+                        mark[t] = Mark::Active;
+                        stack.push((t, 0));
+                    }
+                    Mark::Active => {
+                        let from = stack.iter().position(|&(s, _)| s == t).unwrap_or(0);
+                        let mut cycle: Vec<&str> = stack[from..].iter().map(|&(s, _)| self.names[s].as_str()).collect();
+                        cycle.push(&self.names[t]);
+                        let help = if cycle.len() == 2 {
+                            format!("`{}` imports itself", cycle[0])
+                        } else {
+                            format!("cycle: {}", cycle.join(" -> "))
+                        };
+                        if let NodeT::ImportNode(imp) = &units[m].parser.get_ast()[node] {
+                            let (line, start, end) = (imp.line, imp.start, imp.end);
+                            units[m].parser.add_err_help(ErrT::ImportCycle, line, start, end, Some(help));
+                        }
+                    }
+                    Mark::Done => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use crate::{driver::load_project, err::def::ErrT, module::layout::Layout};
+
+    #[test]
+    fn import_cycle_reports_the_chain() {
+        let dir = std::env::temp_dir().join(format!("sip-graph-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (name, imports) in [("a", "b"), ("b", "c"), ("c", "a"), ("d", "d")] {
+            fs::write(dir.join(format!("{}.sip", name)), format!("import {};\n", imports)).unwrap();
+        }
+        let p = load_project(Path::new(&dir), Layout::single(dir.clone()), None).unwrap();
+        let cycles: Vec<(&str, &str)> = p.units.iter().zip(&p.graph.names)
+            .flat_map(|(u, name)| u.parser.errs.iter().map(move |e| (name, e)))
+            .filter(|(_, e)| matches!(e.et, ErrT::ImportCycle))
+            .map(|(name, e)| (name.as_str(), e.help.as_deref().unwrap_or_default()))
+            .collect();
+        assert_eq!(cycles, [("c", "cycle: a -> b -> c -> a"), ("d", "`d` imports itself")]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
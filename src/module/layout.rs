@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+use super::{mod_file, mod_path};
+
+///a package as far as module paths are concerned
+#[derive(Debug, Clone)]
+pub struct PkgRoot {
+    ///`None` for the package being built, whose modules are not prefixed
+    pub name: Option<String>,
+    ///directory module paths are relative to
+    pub dir: PathBuf,
+    ///the file `import <name>;` refers to
+    pub entry: Option<PathBuf>,
+    ///names of the packages this one may import
+    pub deps: Vec<String>,
+}
+
+///maps files to module paths and `import`s to files across packages
+///
+/// modules of a dependency `util` are named `util.<path>`, its entry is just `util`
+#[derive(Debug, Clone)]
+pub struct Layout {
+    ///`pkgs[0]` is the package being built
+    pub pkgs: Vec<PkgRoot>,
+}
+
+impl Layout {
+    ///a single package without dependencies rooted at `dir`
+    pub fn single(dir: PathBuf) -> Self {
+        Self { pkgs: vec![PkgRoot { name: None, dir, entry: None, deps: Vec::new() }] }
+    }
+
+    ///package a file belongs to, the one with the deepest root containing it
+    pub fn pkg_of(&self, file: &Path) -> usize {
+        self.pkgs.iter().enumerate()
+            .filter(|(_, p)| file.starts_with(&p.dir))
+            .max_by_key(|(_, p)| p.dir.components().count())
+            .map_or(0, |(i, _)| i)
+    }
+
+    ///canonical module path of a file
+    pub fn mod_path(&self, file: &Path) -> String {
+        let pkg = &self.pkgs[self.pkg_of(file)];
+        let rel = mod_path(&pkg.dir, file);
+        match &pkg.name {
+            None => rel,
+            Some(name) if pkg.entry.as_deref() == Some(file) => name.clone(),
+            Some(name) => format!("{}.{}", name, rel),
+        }
+    }
+
+    ///canonical module path and file of `import path;` written in `from`
+    pub fn import(&self, from: &Path, path: &[String]) -> (String, PathBuf) {
+        let pkg = &self.pkgs[self.pkg_of(from)];
+        let dep = path.first()
+            .filter(|first| pkg.deps.contains(first))
+            .and_then(|first| self.pkgs.iter().find(|p| p.name.as_ref() == Some(first)));
+        match dep {
+            Some(dep) => {
+                let file = match (&path[1..], &dep.entry) {
+                    ([], Some(entry)) => entry.clone(),
+                    (rest, _) => mod_file(&dep.dir, rest),
+                };
+                (self.mod_path(&file), file)
+            }
+            None => {
+                let file = mod_file(&pkg.dir, path);
+                let name = match &pkg.name {
+                    None => path.join("."),
+                    Some(n) => format!("{}.{}", n, path.join(".")),
+                };
+                (name, file)
+            }
+        }
+    }
+}
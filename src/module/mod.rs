@@ -0,0 +1,26 @@
+pub mod graph;
+pub mod layout;
+pub mod resolve;
+
+use std::path::{Path, PathBuf};
+
+use crate::driver::EXT;
+
+///module path of `file` relative to `root`: `root/util/math.sip` -> `util.math`
+pub fn mod_path(root: &Path, file: &Path) -> String {
+    let rel = file.strip_prefix(root).unwrap_or(file).with_extension("");
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+///file a module path points at, the inverse of `mod_path`
+pub fn mod_file(root: &Path, path: &[String]) -> PathBuf {
+    let mut file = root.to_path_buf();
+    for seg in path {
+        file.push(seg);
+    }
+    file.set_extension(EXT);
+    file
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::defs::NodeT,
+    driver::Unit,
+    timings::Phase,
+};
+
+use super::graph::ModGraph;
+
+///a function somewhere in the project: module index and node index in its ast
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FnRef {
+    pub module: usize,
+    pub node: usize,
+}
+
+///the names a single module can see
+#[derive(Debug, Default)]
+pub struct Namespace {
+    ///every fn defined in the module
+    pub locals: HashMap<String, FnRef>,
+    ///the `pub` subset of `locals`, what importers see
+    pub exports: HashMap<String, FnRef>,
+    ///imported modules as `(module path, module)`
+    pub imports: Vec<(String, usize)>,
+}
+
+///result of looking a name up from inside a module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lookup {
+    Found(FnRef),
+    ///exists in an imported module but is not `pub`
+    Private(FnRef),
+    ///exported by more than one imported module
+    Ambiguous(Vec<FnRef>),
+    NotFound,
+}
+
+fn namespace(graph: &ModGraph, m: usize, ast: &[NodeT]) -> Namespace {
+    let mut ns = Namespace::default();
+    for (n, node) in ast.iter().enumerate() {
+        if let NodeT::FUNCNode(f) = node {
+            let r = FnRef { module: m, node: n };
+            // the first definition wins, duplicates are reported elsewhere
+            ns.locals.entry(f.name.clone()).or_insert(r);
+            if f.public {
+                ns.exports.entry(f.name.clone()).or_insert(r);
+            }
+        }
+    }
+    ns.imports = graph.edges[m].iter().map(|&(t, _)| (graph.names[t].clone(), t)).collect();
+    ns
+}
+
+///per-module namespaces for a whole project
+#[derive(Debug, Default)]
+pub struct Resolver {
+    pub spaces: Vec<Namespace>,
+}
+
+impl Resolver {
+    ///build a namespace for every module, timed as the namespace phase of its unit
+    pub fn new(graph: &ModGraph, units: &mut [Unit]) -> Self {
+        let spaces = units.iter_mut().enumerate().map(|(m, unit)| {
+            let ast = unit.parser.get_ast();
+            unit.times.time(Phase::Namespace, || namespace(graph, m, ast))
+        }).collect();
+        Self { spaces }
+    }
+
+    ///same as `new`, for asts that do not come from files
+    pub fn from_asts(graph: &ModGraph, asts: &[Vec<NodeT>]) -> Self {
+        Self { spaces: asts.iter().enumerate().map(|(m, ast)| namespace(graph, m, ast)).collect() }
+    }
+
+    ///look up `path` from inside `module`
+    ///
+    /// a single segment is searched in the module itself, then in the exports of
+    /// every import. a qualified `util.add` is searched only in the import whose
+    /// path (or last path segment) is `util`.
+    pub fn lookup(&self, module: usize, path: &[&str]) -> Lookup {
+        let Some((&name, qual)) = path.split_last() else { return Lookup::NotFound };
+        let ns = &self.spaces[module];
+
+        if qual.is_empty() && let Some(&r) = ns.locals.get(name) {
+            return Lookup::Found(r);
+        }
+        let qual = qual.join(".");
+        let imports = ns.imports.iter().filter(|(p, _)| {
+            qual.is_empty() || *p == qual || p.rsplit('.').next() == Some(qual.as_str())
+        });
+
+        let mut found = Vec::new();
+        let mut private = None;
+        for &(_, t) in imports {
+            let space = &self.spaces[t];
+            if let Some(&r) = space.exports.get(name) {
+                if !found.contains(&r) {
+                    found.push(r);
+                }
+            } else if let Some(&r) = space.locals.get(name) {
+                private.get_or_insert(r);
+            }
+        }
+        match (found.len(), private) {
+            (1, _) => Lookup::Found(found[0]),
+            (0, Some(r)) => Lookup::Private(r),
+            (0, None) => Lookup::NotFound,
+            _ => Lookup::Ambiguous(found),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///`main` importing `util` and `math.ops`, which both export `both`
+    fn resolver() -> Resolver {
+        let srcs = [
+            "import util;\nimport math.ops;\nfn main() @ i32 { 1 }",
+            "pub fn add(i32 a) @ i32 { a }\nfn secret() @ i32 { 1 }\npub fn both() @ i32 { 2 }",
+            "pub fn mul(i32 a) @ i32 { a }\npub fn both() @ i32 { 3 }",
+        ];
+        let asts: Vec<Vec<NodeT>> = srcs.iter().map(|s| crate::parse(s).get_ast().clone()).collect();
+        let graph = ModGraph {
+            names: vec!["main".into(), "util".into(), "math.ops".into()],
+            edges: vec![vec![(1, 0), (2, 1)], Vec::new(), Vec::new()],
+        };
+        Resolver::from_asts(&graph, &asts)
+    }
+
+    #[test]
+    fn private_items_stay_in_their_module() {
+        let r = resolver();
+        assert_eq!(r.lookup(0, &["secret"]), Lookup::Private(FnRef { module: 1, node: 1 }));
+        assert_eq!(r.lookup(0, &["util", "secret"]), Lookup::Private(FnRef { module: 1, node: 1 }));
+        // inside its own module it is an ordinary local
+        assert_eq!(r.lookup(1, &["secret"]), Lookup::Found(FnRef { module: 1, node: 1 }));
+    }
+
+    #[test]
+    fn glob_lookup_is_ambiguous_across_imports() {
+        let r = resolver();
+        assert_eq!(r.lookup(0, &["both"]), Lookup::Ambiguous(vec![FnRef { module: 1, node: 2 }, FnRef { module: 2, node: 1 }]));
+        assert_eq!(r.lookup(0, &["add"]), Lookup::Found(FnRef { module: 1, node: 0 }));
+        assert_eq!(r.lookup(0, &["nope"]), Lookup::NotFound);
+    }
+
+    #[test]
+    fn qualified_paths_pick_one_import() {
+        let r = resolver();
+        assert_eq!(r.lookup(0, &["util", "both"]), Lookup::Found(FnRef { module: 1, node: 2 }));
+        // by the full module path or its last segment
+        assert_eq!(r.lookup(0, &["math", "ops", "both"]), Lookup::Found(FnRef { module: 2, node: 1 }));
+        assert_eq!(r.lookup(0, &["ops", "mul"]), Lookup::Found(FnRef { module: 2, node: 0 }));
+        assert_eq!(r.lookup(0, &["util", "mul"]), Lookup::NotFound);
+        // a module that is not imported is not searched
+        assert_eq!(r.lookup(1, &["ops", "mul"]), Lookup::NotFound);
+    }
+}
@@ -0,0 +1,132 @@
+use super::Parser;
+use crate::{
+    ast::defs::{BinOp, Expr, ExprT, UnOp, VarT},
+    err::def::ErrT,
+    token::defs::{IDType, TT},
+};
+
+impl Parser {
+    ///an expression starting at `cur`, `None` after reporting an error if there is none
+    pub fn pexpr(&mut self) -> Option<Expr> {
+        self.pbinary(0)
+    }
+
+    fn binop(&self) -> Option<BinOp> {
+        BinOp::from_symbol(self.cur.text())
+    }
+
+    ///operators binding tighter than `min`, left to right
+    fn pbinary(&mut self, min: u8) -> Option<Expr> {
+        let mut lhs = self.pcast()?;
+        while let Some(op) = self.binop().filter(|op| op.prec() > min) {
+            self.mv();
+            let rhs = self.pbinary(op.prec())?;
+            lhs = Expr {
+                line: lhs.line,
+                start: lhs.start,
+                end: rhs.end,
+                kind: ExprT::Binary(op, Box::new(lhs), Box::new(rhs)),
+            };
+        }
+        Some(lhs)
+    }
+
+    ///`x as i64 as f32`, binds looser than unary operators
+    fn pcast(&mut self) -> Option<Expr> {
+        let mut e = self.punary()?;
+        while self.cur.TT == TT::As {
+            self.mv();
+            let ty = match self.cur.TT {
+                TT::I32 | TT::I64 | TT::U8 | TT::F32 | TT::F64 => VarT::from_name(self.cur.text()),
+                _ => None,
+            };
+            let Some(ty) = ty else {
+                let help = self.type_suggestion(&self.cur);
+                self.add_err_help(ErrT::SyntaxErr("expected a type after `as`"), self.cur.line, self.cur.start, self.cur.end, help);
+                return None;
+            };
+            e = Expr { line: e.line, start: e.start, end: self.cur.end, kind: ExprT::Cast(Box::new(e), ty) };
+            self.mv();
+        }
+        Some(e)
+    }
+
+    fn punary(&mut self) -> Option<Expr> {
+        let Some(op) = UnOp::from_symbol(self.cur.text()) else { return self.pprimary() };
+        let tok = self.cur.clone();
+        self.mv();
+        let e = self.punary()?;
+        Some(Expr { line: tok.line, start: tok.start, end: e.end, kind: ExprT::Unary(op, Box::new(e)) })
+    }
+
+    fn pprimary(&mut self) -> Option<Expr> {
+        let tok = self.cur.clone();
+        match (&tok.TT, &tok.value) {
+            (TT::NUM, Some(n)) => {
+                self.mv();
+                Some(Expr { kind: ExprT::Num(n.clone()), line: tok.line, start: tok.start, end: tok.end })
+            }
+            (TT::True | TT::False, _) => {
+                self.mv();
+                Some(Expr { kind: ExprT::Bool(tok.TT == TT::True), line: tok.line, start: tok.start, end: tok.end })
+            }
+            (TT::LSmallB, _) => {
+                self.mv();
+                let mut e = self.pexpr()?;
+                if self.cur.TT != TT::RsmallB {
+                    self.add_err(ErrT::SyntaxErr("expected ')'"), self.cur.line, self.cur.start, self.cur.end);
+                    return None;
+                }
+                (e.start, e.end) = (tok.start, self.cur.end);
+                self.mv();
+                Some(e)
+            }
+            (TT::Ident(IDType::Nil), Some(_)) => self.pname(),
+            (TT::If, _) => self.pif(),
+            _ => {
+                self.add_err(ErrT::SyntaxErr("expected an expression"), tok.line, tok.start, tok.end);
+                None
+            }
+        }
+    }
+
+    ///`a`, `f(args)` or `util.f(args)`
+    fn pname(&mut self) -> Option<Expr> {
+        let first = self.cur.clone();
+        let mut path = Vec::new();
+        let mut end = first.end;
+        while let (TT::Ident(IDType::Nil), Some(seg)) = (&self.cur.TT, &self.cur.value) {
+            path.push(seg.clone());
+            end = self.cur.end;
+            self.mv();
+            if self.cur.TT != TT::Dot {
+                break;
+            }
+            self.mv();
+        }
+
+        if self.cur.TT != TT::LSmallB {
+            if path.len() > 1 {
+                self.add_err(ErrT::SyntaxErr("expected '(' after a qualified name"), self.cur.line, self.cur.start, self.cur.end);
+                return None;
+            }
+            return Some(Expr { kind: ExprT::Ident(path.remove(0)), line: first.line, start: first.start, end });
+        }
+        self.mv();
+        let mut args = Vec::new();
+        while self.cur.TT != TT::RsmallB {
+            args.push(self.pexpr()?);
+            match self.cur.TT {
+                TT::Comma => self.mv(),
+                TT::RsmallB => {}
+                _ => {
+                    self.add_err(ErrT::SyntaxErr("expected ',' or ')' after an argument"), self.cur.line, self.cur.start, self.cur.end);
+                    return None;
+                }
+            }
+        }
+        end = self.cur.end;
+        self.mv();
+        Some(Expr { kind: ExprT::Call(path, args), line: first.line, start: first.start, end })
+    }
+}
@@ -1,14 +1,15 @@
 use super::Parser;
 use crate::{
-    ast::defs::{FUNC, NodeT, Var, VarT},
-    err::def::ErrT,
-    token::defs::{IDType, TT, Token},
+    ast::defs::{FUNC, NodeT, Ret, Var, VarT},
+    err::{def::ErrT, suggest::{did_you_mean, suggest}},
+    token::{defs::{IDType, TT, Token}, lexer::TYPE_NAMES},
 };
-use std::{borrow::Cow, process::exit};
+use std::borrow::Cow;
 
 #[allow(unused)]
 impl Parser {
-    pub fn pfunc(&mut self) {
+    pub fn pfunc(&mut self, public: bool) {
+        let name_tok = self.cur.clone();
         let fname = match &self.cur.TT {
             TT::Ident(id_type) if *id_type != IDType::DQ && *id_type != IDType::SQ => {
                 self.token_val().unwrap_or(Cow::Borrowed("_"))
@@ -27,73 +28,115 @@ impl Parser {
         self.mv();
 
         let mut params = Vec::new();
-        while self.cur.TT != TT::RsmallB {
-            let param_type = self.cur.clone();
-            self.mv();
-
-            if self.cur.TT == TT::Ident(IDType::Nil) {
-                if let Some(name) = self.token_val() {
-                    params.push((param_type, name));
+        while !matches!(self.cur.TT, TT::RsmallB | TT::EOF) {
+            // `name: type` or `type name`
+            let named_first = self.cur.TT == TT::Ident(IDType::Nil) && self.peek().is_some_and(|t| t.TT == TT::Colon);
+            // `name type`, the `:` is missing
+            let no_colon = self.cur.TT == TT::Ident(IDType::Nil)
+                && self.peek().is_some_and(|t| matches!(t.TT, TT::F32 | TT::F64 | TT::I32 | TT::I64 | TT::U8 | TT::Bool));
+            let (ty, name_tok) = if named_first || no_colon {
+                let name_tok = self.cur.clone();
+                self.mv();
+                if no_colon {
+                    self.add_err(ErrT::SyntaxErr("expected `:` between the parameter name and its type"), self.cur.line, self.cur.start, self.cur.end);
                 } else {
-                    self.add_err(ErrT::ExpectParamName, self.cur.line, self.cur.start, self.cur.end);
+                    self.mv();
                 }
-                self.mv();
+                (self.ptype(false), Some(name_tok))
             } else {
-                self.add_err(ErrT::ExpectParamName, self.cur.line, self.cur.start, self.cur.end);
+                let ty = self.ptype(true);
+                let name_tok = (self.cur.TT == TT::Ident(IDType::Nil)).then(|| self.cur.clone());
+                match &name_tok {
+                    Some(_) => self.mv(),
+                    None => self.add_err(ErrT::ExpectParamName, self.cur.line, self.cur.start, self.cur.end),
+                }
+                (ty, name_tok)
+            };
+            if let Some(name_tok) = name_tok {
+                params.push((ty, name_tok));
             }
 
-            if self.cur.TT == TT::Comma {
-                self.mv();
+            match self.cur.TT {
+                TT::Comma => self.mv(),
+                TT::RsmallB => {}
+                _ if self.pos >= self.tokens.len() => {}
+                // keep going from the next parameter
+                _ => {
+                    self.add_err(ErrT::SyntaxErr("expected ',' or ')'"), self.cur.line, self.cur.start, self.cur.end);
+                    while !matches!(self.cur.TT, TT::Comma | TT::RsmallB | TT::LCurlyB | TT::EOF) {
+                        self.mv();
+                    }
+                    if self.cur.TT == TT::Comma {
+                        self.mv();
+                    }
+                }
             }
-
-            if self.pos >= self.tokens.len() {
-                self.add_err(ErrT::SyntaxErr("unexpected end of input"), self.cur.line, self.cur.start, self.cur.end);
+            if matches!(self.cur.TT, TT::LCurlyB | TT::EOF) {
                 break;
             }
         }
-
-        self.mv();
-
-        if self.cur.TT != TT::At {
-            self.add_err(ErrT::ExpectedAT, self.cur.line, self.cur.start, self.cur.end);
+        if self.cur.TT == TT::EOF {
+            self.add_err(ErrT::SyntaxErr("unexpected end of input"), self.cur.line, self.cur.start, self.cur.end);
         }
-        self.mv();
 
-        let mut ret_type = Token { TT: TT::I32, line: 0, start: 0, end: 0, value: None }; // Default type
+        self.mv();
 
-        // Check for valid return type
-        if matches!(self.cur.TT, TT::F32 | TT::F64 | TT::I32 | TT::I64 | TT::U8) {
-            ret_type = self.cur.clone(); // Update ret_type if matched
-            self.mv();
+        let ret_type = if self.cur.TT == TT::LCurlyB {
+            // no `@` clause, the fn returns nothing
+            Token { TT: TT::Nil, line: self.cur.line, start: self.cur.start, end: self.cur.start, value: Some("nil".into()) }
         } else {
-            self.add_err(ErrT::SyntaxErr("Expected valid return type"), self.cur.line, self.cur.start, self.cur.end);
-        }
+            if self.cur.TT != TT::At {
+                self.add_err(ErrT::ExpectedAT, self.cur.line, self.cur.start, self.cur.end);
+            }
+            self.mv();
+            // Check for valid return type
+            if matches!(self.cur.TT, TT::F32 | TT::F64 | TT::I32 | TT::I64 | TT::U8 | TT::Bool | TT::Nil) {
+                let tok = self.cur.clone();
+                self.mv();
+                tok
+            } else {
+                let help = self.type_suggestion(&self.cur).or_else(|| Some("a return type is one of i32, i64, u8, f32, f64, bool or nil".into()));
+                self.add_err_help(ErrT::SyntaxErr("Expected valid return type"), self.cur.line, self.cur.start, self.cur.end, help);
+                if self.cur.TT == TT::Ident(IDType::Nil) {
+                    self.mv();
+                }
+                Token { TT: TT::I32, line: 0, start: 0, end: 0, value: Some("i32".into()) } // Default type
+            }
+        };
 
         if self.cur.TT != TT::LCurlyB {
             self.add_err(ErrT::SyntaxErr("expected { to start function body"), self.cur.line, self.cur.start, self.cur.end);
         }
         self.mv();
 
-        let body_tokens = self.collect_block_tokens();
-
-        // Validate return type against function body
-        self.validate_return_type(&body_tokens, &ret_type);
+        let (body, _) = self.pbody("expected '}' to close the function body");
+        let ret_var = self.map_token_to_vartype(&ret_type);
 
         let func_node = FUNC {
             name: fname.into_owned(),
             args: (!params.is_empty()).then_some(
-                params.into_iter().map(|(tok, name)| Var {
-                    name: name.into_owned(),
+                params.into_iter().map(|(ty, name_tok)| Var {
+                    name: name_tok.value.clone().unwrap_or_default(),
                     dyna: false,
-                    TT: self.map_token_to_vartype(&tok),
+                    TT: ty,
+                    line: name_tok.line,
+                    start: name_tok.start,
+                    end: name_tok.end,
                 }).collect()
             ),
-            body: vec![], // TODO: parse body tokens into AST
+            body,
             ret: Var {
                 name: self.token_val_from(&ret_type).unwrap_or_else(|| "_".into()).into_owned(),
                 dyna: false,
-                TT: self.map_token_to_vartype(&ret_type),
+                TT: ret_var,
+                line: ret_type.line,
+                start: ret_type.start,
+                end: ret_type.end,
             },
+            public,
+            line: name_tok.line,
+            start: name_tok.start,
+            end: name_tok.end,
         };
 
         self.add_node(NodeT::FUNCNode(func_node));
@@ -107,82 +150,97 @@ impl Parser {
         tok.value.as_ref().map(|v| Cow::Owned(v.clone()))
     }
 
-    fn collect_block_tokens(&mut self) -> Vec<Token> {
-        let mut body_tokens = Vec::new();
-        let mut brace_count = 1;
-
-        while brace_count > 0 {
-            if self.pos >= self.tokens.len() {
-                self.add_err(ErrT::SyntaxErr("unexpected end of function body"), self.cur.line, self.cur.start, self.cur.end);
-                break;
-            }
-
-            let tok = self.cur.clone();
-            match tok.TT {
-                TT::LCurlyB => brace_count += 1,
-                TT::RCurlyB => {
-                    brace_count -= 1;
-                    if brace_count == 0 {
-                        self.mv();
-                        break;
-                    }
-                }
-                _ => {}
-            }
-
-            body_tokens.push(tok);
+    ///a parameter or local type, reported at the offending token if it is not one
+    ///
+    /// a bad type still yields `i32` so the name stays declared.
+    /// `before_name` is set for `type name`, where `(x)` lacks the type instead
+    pub(super) fn ptype(&mut self, before_name: bool) -> VarT {
+        let tok = self.cur.clone();
+        if matches!(tok.TT, TT::F32 | TT::F64 | TT::I32 | TT::I64 | TT::U8 | TT::Bool) {
+            self.mv();
+            return self.map_token_to_vartype(&tok);
+        }
+        let help = self.type_suggestion(&tok).or_else(|| Some(format!("a type is one of {}", TYPE_NAMES.join(", "))));
+        self.add_err_help(ErrT::InvVT, tok.line, tok.start, tok.end, help);
+        let missing = before_name && self.peek().is_some_and(|t| matches!(t.TT, TT::Comma | TT::RsmallB));
+        if !missing && !matches!(tok.TT, TT::Comma | TT::RsmallB | TT::LCurlyB | TT::EOF) {
             self.mv();
         }
-        body_tokens
+        VarT::I32(0)
     }
 
-    fn validate_return_type(&mut self, body: &[Token], ret_type: &Token) {
-        let mut return_found = false;
-
-        for (i, token) in body.iter().enumerate() {
-            if token.TT == TT::Ret {
-                return_found = true;
-
-                if let Some(ret_val) = body.get(i + 1) {
-                    if !self.validate_token_against_return_type(ret_val, ret_type) {
-                        self.add_err(ErrT::RetTMisMatch("Return type mismatch"), self.cur.line, self.cur.start, self.cur.end);
-                    }
-                    return;
-                } else {
-                    self.add_err(ErrT::RetTMisMatch("unexpected return value"), self.cur.line, self.cur.start, self.cur.end);
-                    return;
-                }
-            }
+    ///"did you mean" help for a token that should have been a type
+    pub(super) fn type_suggestion(&self, tok: &Token) -> Option<String> {
+        match (&tok.TT, &tok.value) {
+            (TT::Ident(IDType::Nil), Some(v)) => suggest(v, TYPE_NAMES.iter().copied()).map(|s| did_you_mean(&s)),
+            _ => None,
         }
+    }
 
-        // Handle case if no return statement is found
-        if !return_found {
-            let last_expr = body.iter().rev().find(|tok| tok.TT != TT::SemiColon);
-            match last_expr {
-                Some(token) if !self.validate_token_against_return_type(token, ret_type) => {
-                    self.add_err(ErrT::RetTMisMatch("Last expr does not match return type"), self.cur.line, self.cur.start, self.cur.end);
+    ///statements up to the closing `}`, called with `cur` right after `{`
+    ///
+    /// returns the body and the closing `}`, if there was one. `unclosed` is
+    /// reported when the file or the next item comes first
+    pub(super) fn pbody(&mut self, unclosed: &'static str) -> (Vec<NodeT>, Option<Token>) {
+        let mut body = Vec::new();
+        loop {
+            match self.cur.TT {
+                TT::RCurlyB => {
+                    let close = self.cur.clone();
+                    self.mv();
+                    return (body, Some(close));
+                }
+                TT::EOF | TT::FN | TT::Pub | TT::Import => {
+                    self.add_err(ErrT::SyntaxErr(unclosed), self.cur.line, self.cur.start, self.cur.end);
+                    return (body, None);
                 }
-                None => {
-                    self.add_err(ErrT::RetTNF, self.cur.line, self.cur.start, self.cur.end);
-                    exit(1);
+                TT::SemiColon => self.mv(),
+                TT::Ret => {
+                    let tok = self.cur.clone();
+                    self.mv();
+                    let value = match self.cur.TT {
+                        TT::SemiColon | TT::RCurlyB | TT::EOF => None,
+                        _ => match self.pexpr() {
+                            Some(e) => Some(e),
+                            None => {
+                                self.skip_bad();
+                                continue;
+                            }
+                        },
+                    };
+                    let end = value.as_ref().map_or(tok.end, |v| v.end);
+                    body.push(NodeT::RetNode(Ret { value, line: tok.line, start: tok.start, end }));
                 }
-                _ => {}
+                TT::While | TT::Loop | TT::For => match self.ploop() {
+                    Some(l) => body.push(l),
+                    None => self.skip_bad(),
+                },
+                TT::Break | TT::Continue => body.push(self.pjump()),
+                // a statement `if` ends at its `}`, it does not continue into an operator on the next line
+                TT::If => match self.pif() {
+                    Some(e) => body.push(NodeT::ExprNode(e)),
+                    None => self.skip_bad(),
+                },
+                TT::Let => match self.plet() {
+                    Some(l) => body.push(l),
+                    None => self.skip_bad(),
+                },
+                _ => match self.pexpr() {
+                    Some(e) if matches!(self.cur.TT, TT::Eqs | TT::OpEq) => match self.passign(e) {
+                        Some(a) => body.push(a),
+                        None => self.skip_bad(),
+                    },
+                    Some(e) => body.push(NodeT::ExprNode(e)),
+                    None => self.skip_bad(),
+                },
             }
         }
     }
 
-    fn validate_token_against_return_type(&mut self, token: &Token, ret_type: &Token) -> bool {
-        let expected = self.token_val_from(ret_type).unwrap_or_else(|| "_".into());
-        match expected.as_ref() {
-            "i32" => token.TT == TT::NUM && token.value.as_ref().and_then(|v| v.parse::<i32>().ok()).is_some(),
-            "i64" => token.TT == TT::NUM && token.value.as_ref().and_then(|v| v.parse::<i64>().ok()).is_some(),
-            "f32" => token.TT == TT::NUM && token.value.as_ref().and_then(|v| v.parse::<f32>().ok()).is_some(),
-            "f64" => token.TT == TT::NUM && token.value.as_ref().and_then(|v| v.parse::<f64>().ok()).is_some(),
-            "u8"  => token.TT == TT::NUM && token.value.as_ref().and_then(|v| v.parse::<u8>().ok()).is_some(),
-            _ => {
-                self.add_err(ErrT::RetTMisMatch("Unsupported return type"), self.cur.line, self.cur.start, self.cur.end);
-                false
-            }
+    ///step over the token an error was reported at, unless it ends the body
+    fn skip_bad(&mut self) {
+        if !matches!(self.cur.TT, TT::RCurlyB | TT::EOF | TT::FN | TT::Pub | TT::Import) {
+            self.mv();
         }
     }
 
@@ -193,8 +251,11 @@ impl Parser {
             TT::F32 | TT::Ident(IDType::Nil) if self.token_val_from(tok).as_deref() == Some("f32") => VarT::F32(0.0),
             TT::F64 | TT::Ident(IDType::Nil) if self.token_val_from(tok).as_deref() == Some("f64") => VarT::F64(0.0),
             TT::U8  | TT::Ident(IDType::Nil) if self.token_val_from(tok).as_deref() == Some("u8")  => VarT::U8(0),
+            TT::Bool => VarT::Bool(false),
+            TT::Nil => VarT::Nil,
             _ => {
-                self.add_err(ErrT::InvVT, self.cur.line, self.cur.start, self.cur.end);
+                let help = self.type_suggestion(tok);
+                self.add_err_help(ErrT::InvVT, tok.line, tok.start, tok.end, help);
                 VarT::I32(0)
             }
         }
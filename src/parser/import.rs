@@ -0,0 +1,39 @@
+use super::Parser;
+use crate::{
+    ast::defs::{Import, NodeT},
+    err::def::ErrT,
+    token::defs::{IDType, TT},
+};
+
+impl Parser {
+    ///`import a.b.c;`, called with `cur` right after `import`
+    pub fn pimport(&mut self) {
+        let (line, start) = (self.cur.line, self.cur.start);
+        let mut end;
+        let mut path = Vec::new();
+        loop {
+            match (&self.cur.TT, &self.cur.value) {
+                (TT::Ident(IDType::Nil), Some(seg)) => {
+                    path.push(seg.clone());
+                    end = self.cur.end;
+                    self.mv();
+                }
+                _ => {
+                    self.add_err(ErrT::SyntaxErr("expected a module name"), self.cur.line, self.cur.start, self.cur.end);
+                    return;
+                }
+            }
+            if self.cur.TT != TT::Dot {
+                break;
+            }
+            self.mv();
+        }
+
+        if self.cur.TT == TT::SemiColon {
+            self.mv();
+        } else {
+            self.add_err(ErrT::SyntaxErr("expected ';' after import"), self.cur.line, self.cur.start, self.cur.end);
+        }
+        self.add_node(NodeT::ImportNode(Import { path, line, start, end }));
+    }
+}
@@ -1,17 +1,54 @@
-use crate::token::defs::TT;
+use crate::{
+    err::{def::ErrT, suggest::{did_you_mean, suggest}},
+    token::{defs::{IDType, TT}, lexer::KEYWORDS},
+};
 
 use super::Parser;
 
 impl Parser {
     pub fn parse(&mut self) {
-        match self.cur.TT {
-            TT::FN => {
-                self.mv();
-                self.pfunc();
-            }
-            _ => {
-                eprintln!("not defined");
+        let open: Vec<_> = self.tokens.iter().filter(|t| t.TT == TT::Unterminated).map(|t| (t.line, t.start, t.end)).collect();
+        for (line, start, end) in open {
+            self.add_err(ErrT::SyntaxErr("unterminated string"), line, start, end);
+        }
+        while self.pos < self.tokens.len() {
+            match self.cur.TT {
+                TT::FN => {
+                    self.mv();
+                    self.pfunc(false);
+                }
+                TT::Pub => {
+                    self.mv();
+                    if self.cur.TT == TT::FN {
+                        self.mv();
+                        self.pfunc(true);
+                    } else {
+                        self.add_err(ErrT::SyntaxErr("expected `fn` after `pub`"), self.cur.line, self.cur.start, self.cur.end);
+                        self.skip_item();
+                    }
+                }
+                TT::Import => {
+                    self.mv();
+                    self.pimport();
+                }
+                TT::CONST | TT::GLOBAL => self.pstatic(),
+                _ => {
+                    let help = match (&self.cur.TT, &self.cur.value) {
+                        (TT::Ident(IDType::Nil), Some(v)) => suggest(v, KEYWORDS.iter().copied()).map(|s| did_you_mean(&s)),
+                        _ => None,
+                    };
+                    self.add_err_help(ErrT::SyntaxErr("expected `fn`, `pub fn`, `import`, `const` or `global`"), self.cur.line, self.cur.start, self.cur.end, help);
+                    self.skip_item();
+                }
             }
         }
+        self.errs.sort_by_key(|e| (e.line, e.start));
+    }
+
+    ///skip to the start of the next item
+    pub(super) fn skip_item(&mut self) {
+        while self.pos < self.tokens.len() && !matches!(self.cur.TT, TT::FN | TT::Pub | TT::Import | TT::CONST | TT::GLOBAL) {
+            self.mv();
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,5 +1,9 @@
 pub mod func;
 pub mod init;
+pub mod import;
+pub mod expr;
+pub mod stmt;
+pub mod statics;
 use crate::{
     ast::defs::NodeT,
     err::def::{ErrT, PErr}, err::edis::d1::diserr,
@@ -43,16 +47,36 @@ impl Parser {
         }
     }
 
+    /// Creates a Parser holding an already built AST, e.g. one loaded from the cache.
+    #[inline(always)]
+    pub fn with_ast(ast: Vec<NodeT>) -> Self {
+        let mut p = Self::new(Vec::new());
+        p.ast = ast;
+        p
+    }
+
+    /// Creates a Parser for source that is not UTF-8, holding one error at the first bad byte.
+    pub fn not_utf8(src: &[u8], e: std::str::Utf8Error) -> Self {
+        let mut p = Self::new(Vec::new());
+        let at = e.valid_up_to();
+        let line = src[..at].iter().filter(|&&b| b == b'\n').count() + 1;
+        p.add_err(ErrT::SyntaxErr("source is not valid UTF-8"), line, at, at + e.error_len().unwrap_or(1));
+        p
+    }
+
     /// Advances the parser to the next token.
     ///
-    /// # Returns
-    ///
-    /// `Some(token)` if there is a next token, or `None` if the end of input has been reached.
+    /// Once the last token is passed, `cur` becomes a `TT::EOF` token placed
+    /// right after it and `pos` equals `tokens.len()`.
     #[inline(always)]
     pub fn mv(&mut self) {
         if self.pos + 1 < self.tokens.len() {
             self.pos += 1;
             self.cur = self.tokens[self.pos].clone();
+        } else if self.pos < self.tokens.len() {
+            let last = &self.tokens[self.tokens.len() - 1];
+            self.cur = Token { TT: TT::EOF, line: last.line, start: last.end, end: last.end, value: None };
+            self.pos = self.tokens.len();
         }
     }
 
@@ -142,17 +166,83 @@ impl Parser {
             et,
             start,
             end,
+            help: None,
+        });
+    }
+    ///add a err with an extra help line (if any) to err list
+    #[inline(always)]
+    pub fn add_err_help(&mut self, et: ErrT, line: usize, start: usize, end: usize, help: Option<String>) {
+        self.errs.push(PErr {
+            line,
+            et,
+            start,
+            end,
+            help,
         });
     }
     pub fn erroccur(&self) -> bool {
         //println!("called erroccur");
-        !self.errs.is_empty()
+        self.errs.iter().any(|e| !e.et.is_warning())
     }
-    pub fn show_errs(&self,code: &memmap2::Mmap) {
-        //println!("calling show errs");
+    ///print every error to stderr, stdout is left to what a command produces
+    pub fn show_errs(&self, file: &str, code: &[u8]) {
         for err in &self.errs {
-           // println!("calling diserr");
-           println!( "{}", diserr(err, &code));
+           eprintln!( "{}", diserr(err, file, code));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::defs::NodeT;
+
+    ///kind and span of every error in `src`
+    fn errs(src: &str) -> Vec<String> {
+        crate::parse(src).errs.iter().map(|e| format!("{:?} {}..{}", e.et, e.start, e.end)).collect()
+    }
+
+    ///names and types of the params of the first fn in `src`
+    fn params(src: &str) -> Vec<String> {
+        match &crate::parse(src).get_ast()[0] {
+            NodeT::FUNCNode(f) => f.args.iter().flatten().map(|a| format!("{}: {:?}", a.name, a.TT)).collect(),
+            n => panic!("not a fn: {n:?}"),
+        }
+    }
+
+    #[test]
+    fn both_param_forms() {
+        let src = "fn f(i32 a, b: f64, bool c) @ i32 { a }";
+        assert!(errs(src).is_empty());
+        assert_eq!(params(src), ["a: I32(0)", "b: F64(0.0)", "c: Bool(false)"]);
+    }
+
+    #[test]
+    fn missing_colon() {
+        assert_eq!(errs("fn f(x i32) @ i32 { x }"), ["SyntaxErr(\"expected `:` between the parameter name and its type\") 7..10"]);
+        // the param is still declared with its type
+        assert_eq!(params("fn f(x i64) @ i32 { 1 }"), ["x: I64(0)"]);
+    }
+
+    #[test]
+    fn bad_type_points_at_the_type() {
+        assert_eq!(errs("fn f(foo x) @ i32 { x }"), ["InvVT 5..8"]);
+        assert_eq!(errs("fn f(\"s\" x) @ i32 { x }"), ["InvVT 5..8"]);
+        assert_eq!(errs("fn f(x: 3) @ i32 { x }"), ["InvVT 8..9"]);
+        // a missing type points at what came instead
+        assert_eq!(errs("fn f(x: ) @ i32 { x }"), ["InvVT 8..9"]);
+        assert_eq!(errs("fn f(i32 a, y) @ i32 { a }"), ["InvVT 12..13"]);
+        let p = crate::parse("fn f(x: i3) @ i32 { x }");
+        assert_eq!(errs("fn f(x: i3) @ i32 { x }"), ["InvVT 8..10"]);
+        assert_eq!(p.errs[0].help.as_deref(), Some("did you mean `i32`?"));
+    }
+
+    #[test]
+    fn duplicate_params() {
+        // the parser keeps both, the second is reported by the checker
+        let src = "fn f(x: i32, i32 x) @ i32 { x }";
+        assert_eq!(params(src).len(), 2);
+        let (et, help) = crate::sema::tests::only(src);
+        assert!(matches!(et, crate::err::def::ErrT::DupParam), "{:?}", et);
+        assert_eq!(help, "`x` is already a parameter of `f`");
+    }
+}
@@ -0,0 +1,44 @@
+use super::Parser;
+use crate::{
+    ast::defs::{NodeT, Static, Var},
+    err::def::ErrT,
+    token::defs::{IDType, TT},
+};
+
+impl Parser {
+    ///`const NAME: type = value;` or `global NAME: type = value;`, called with `cur` at the keyword
+    pub fn pstatic(&mut self) {
+        let tok = self.cur.clone();
+        let global = tok.TT == TT::GLOBAL;
+        self.mv();
+        let name = self.cur.clone();
+        if name.TT != TT::Ident(IDType::Nil) {
+            let msg = if global { "expected a name after `global`" } else { "expected a name after `const`" };
+            self.add_err(ErrT::SyntaxErr(msg), name.line, name.start, name.end);
+            return self.skip_item();
+        }
+        self.mv();
+        if self.cur.TT != TT::Colon {
+            self.add_err(ErrT::SyntaxErr("expected `:` and a type after the name"), self.cur.line, self.cur.start, self.cur.end);
+            return self.skip_item();
+        }
+        self.mv();
+        let ty = self.ptype(false);
+        if self.cur.TT != TT::Eqs {
+            self.add_err(ErrT::SyntaxErr("expected `=` and a value"), self.cur.line, self.cur.start, self.cur.end);
+            return self.skip_item();
+        }
+        self.mv();
+        let Some(value) = self.pexpr() else {
+            return self.skip_item();
+        };
+        if self.cur.TT == TT::SemiColon {
+            self.mv();
+        } else {
+            self.add_err(ErrT::SyntaxErr("expected ';' after the value"), self.cur.line, self.cur.start, self.cur.end);
+        }
+        let var = Var { name: name.text().to_string(), dyna: global, TT: ty, line: name.line, start: name.start, end: name.end };
+        let st = Static { var, line: tok.line, start: tok.start, end: value.end, value };
+        self.add_node(if global { NodeT::GlobalNode(st) } else { NodeT::ConstNode(st) });
+    }
+}
@@ -0,0 +1,131 @@
+use super::Parser;
+use crate::{
+    ast::defs::{Assign, BinOp, Expr, ExprT, Jump, JumpT, Let, Loop, LoopT, NodeT, Var, VarT},
+    err::def::ErrT,
+    token::defs::{IDType, TT},
+};
+
+impl Parser {
+    ///`{ .. }` of an `if` or a loop and the end of its `}`, `None` if it does not open or close
+    fn pblock(&mut self) -> Option<(Vec<NodeT>, usize)> {
+        if self.cur.TT != TT::LCurlyB {
+            self.add_err(ErrT::SyntaxErr("expected '{' to start a block"), self.cur.line, self.cur.start, self.cur.end);
+            return None;
+        }
+        self.mv();
+        let (body, close) = self.pbody("expected '}' to close the block");
+        Some((body, close?.end))
+    }
+
+    ///`if cond { .. }`, optionally followed by `else { .. }` or `else if ..`
+    pub(super) fn pif(&mut self) -> Option<Expr> {
+        let tok = self.cur.clone();
+        self.mv();
+        let cond = self.pexpr()?;
+        let (then, mut end) = self.pblock()?;
+        let mut els = None;
+        if self.cur.TT == TT::Else {
+            self.mv();
+            let body = if self.cur.TT == TT::If {
+                let e = self.pif()?;
+                end = e.end;
+                vec![NodeT::ExprNode(e)]
+            } else {
+                let (body, e) = self.pblock()?;
+                end = e;
+                body
+            };
+            els = Some(body);
+        }
+        Some(Expr { kind: ExprT::If(Box::new(cond), then, els), line: tok.line, start: tok.start, end })
+    }
+
+    ///`while`, `loop` or `for` at `cur`
+    pub(super) fn ploop(&mut self) -> Option<NodeT> {
+        let tok = self.cur.clone();
+        self.mv();
+        let kind = match tok.TT {
+            TT::While => LoopT::While(self.pexpr()?),
+            TT::For => self.pfor()?,
+            _ => LoopT::Forever,
+        };
+        let (body, _) = self.pblock()?;
+        Some(NodeT::LoopNode(Loop { kind, body, line: tok.line, start: tok.start, end: tok.end }))
+    }
+
+    ///`x in a..b` of a `for`
+    fn pfor(&mut self) -> Option<LoopT> {
+        let name = self.cur.clone();
+        if name.TT != TT::Ident(IDType::Nil) {
+            self.add_err(ErrT::SyntaxErr("expected a loop variable after `for`"), name.line, name.start, name.end);
+            return None;
+        }
+        self.mv();
+        if self.cur.TT != TT::In {
+            self.add_err(ErrT::SyntaxErr("expected `in` after the loop variable"), self.cur.line, self.cur.start, self.cur.end);
+            return None;
+        }
+        self.mv();
+        let from = self.pexpr()?;
+        if self.cur.TT != TT::DotDot {
+            self.add_err(ErrT::SyntaxErr("expected `..` between the bounds of the range"), self.cur.line, self.cur.start, self.cur.end);
+            return None;
+        }
+        self.mv();
+        let to = self.pexpr()?;
+        // the real type comes from the bounds, the type checker works it out
+        let var = Var { name: name.text().to_string(), dyna: false, TT: VarT::I32(0), line: name.line, start: name.start, end: name.end };
+        Some(LoopT::For { var, from, to })
+    }
+
+    ///`break` or `continue` at `cur`
+    pub(super) fn pjump(&mut self) -> NodeT {
+        let kind = if self.cur.TT == TT::Break { JumpT::Break } else { JumpT::Continue };
+        let j = Jump { kind, line: self.cur.line, start: self.cur.start, end: self.cur.end };
+        self.mv();
+        NodeT::JumpNode(j)
+    }
+
+    ///`let name: type = value` or `let mut ..`, at `let`
+    pub(super) fn plet(&mut self) -> Option<NodeT> {
+        let tok = self.cur.clone();
+        self.mv();
+        let dyna = self.cur.TT == TT::Mut;
+        if dyna {
+            self.mv();
+        }
+        let name = self.cur.clone();
+        if name.TT != TT::Ident(IDType::Nil) {
+            self.add_err(ErrT::SyntaxErr("expected a name after `let`"), name.line, name.start, name.end);
+            return None;
+        }
+        self.mv();
+        if self.cur.TT != TT::Colon {
+            self.add_err(ErrT::SyntaxErr("expected `:` and a type after the name"), self.cur.line, self.cur.start, self.cur.end);
+            return None;
+        }
+        self.mv();
+        let ty = self.ptype(false);
+        if self.cur.TT != TT::Eqs {
+            self.add_err(ErrT::SyntaxErr("expected `=` and a value"), self.cur.line, self.cur.start, self.cur.end);
+            return None;
+        }
+        self.mv();
+        let value = self.pexpr()?;
+        let var = Var { name: name.text().to_string(), dyna, TT: ty, line: name.line, start: name.start, end: name.end };
+        Some(NodeT::LetNode(Let { var, line: tok.line, start: tok.start, end: value.end, value }))
+    }
+
+    ///`target = value` or `target += value`, `cur` is at the `=`
+    pub(super) fn passign(&mut self, target: Expr) -> Option<NodeT> {
+        // `+=` is `+` and `=`
+        let op = BinOp::from_symbol(self.cur.text().get(..1).unwrap_or_default()).filter(|_| self.cur.TT == TT::OpEq);
+        self.mv();
+        let value = self.pexpr()?;
+        if !matches!(target.kind, ExprT::Ident(_)) {
+            self.add_err(ErrT::SyntaxErr("only a name can be assigned to"), target.line, target.start, target.end);
+            return None;
+        }
+        Some(NodeT::AssignNode(Assign { line: target.line, start: target.start, end: value.end, target, op, value }))
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::defs::{BinOp, Expr, ExprT, NodeT, UnOp, VarT, tail},
+    err::def::{ErrT, PErr},
+    interp::{RunErr, value},
+};
+
+use super::{Bindings, Decl};
+
+struct Folder<'a> {
+    ast: &'a [NodeT],
+    binds: &'a Bindings,
+    ///values by node index, `None` if it could not be folded
+    done: HashMap<usize, Option<VarT>>,
+    ///nodes being folded, to catch consts defined through themselves
+    busy: Vec<usize>,
+    errs: Vec<PErr>,
+}
+
+impl Folder<'_> {
+    fn err(&mut self, e: &Expr, help: String) {
+        self.errs.push(PErr { et: ErrT::ConstEval, line: e.line, start: e.start, end: e.end, help: Some(help) });
+    }
+
+    ///value of the const or global at node `n`
+    fn node(&mut self, n: usize) -> Option<VarT> {
+        if let Some(v) = self.done.get(&n) {
+            return v.clone();
+        }
+        let (NodeT::ConstNode(s) | NodeT::GlobalNode(s)) = &self.ast[n] else { return None };
+        if self.busy.contains(&n) {
+            self.err(&s.value, format!("the value of `{}` depends on itself", s.var.name));
+            return None;
+        }
+        self.busy.push(n);
+        let v = self.eval(&s.value, Some(&s.var.TT));
+        self.busy.pop();
+        // a value of the wrong type is left to the type checker
+        let v = v.filter(|v| v.same_type(&s.var.TT));
+        self.done.insert(n, v.clone());
+        v
+    }
+
+    ///a failed operation as a diagnostic, type errors are left to the type checker
+    fn fail(&mut self, e: &Expr, ty: &VarT, r: RunErr) -> Option<VarT> {
+        match r {
+            RunErr::Overflow { .. } => self.err(e, format!("the value of `{}` does not fit in {}", e, ty.name())),
+            RunErr::DivZero { .. } => self.err(e, format!("`{}` divides by zero", e)),
+            _ => {}
+        }
+        None
+    }
+
+    ///value of `e`, untyped literals take the type of `hint`
+    fn eval(&mut self, e: &Expr, hint: Option<&VarT>) -> Option<VarT> {
+        match &e.kind {
+            // a literal that does not fit its type is reported by the type checker
+            ExprT::Num(n) => value::literal(n, hint, e.line).ok(),
+            ExprT::Bool(b) => Some(VarT::Bool(*b)),
+            ExprT::Ident(name) => match self.binds.uses.get(&e.start) {
+                Some(&Decl::Const(n)) => self.node(n),
+                Some(Decl::Global(_)) => {
+                    self.err(e, format!("`{}` is a global and can change while the program runs", name));
+                    None
+                }
+                _ => None,
+            },
+            ExprT::Unary(UnOp::Neg, x) if let ExprT::Num(n) = &x.kind => value::literal(&format!("-{}", n), hint, e.line).ok(),
+            ExprT::Unary(op, x) => {
+                let v = self.eval(x, hint)?;
+                value::unary(*op, &v, e.line).map_or_else(|r| self.fail(e, &v, r), Some)
+            }
+            ExprT::Binary(op @ (BinOp::And | BinOp::Or), l, r) => {
+                let lv = self.eval(l, None)?;
+                // the right side is not folded when the left decides, as it would not run either
+                match lv {
+                    VarT::Bool(b) if b == (*op == BinOp::Or) => Some(lv),
+                    VarT::Bool(_) => self.eval(r, None),
+                    _ => None,
+                }
+            }
+            ExprT::Binary(op, l, r) => {
+                let hint = hint.filter(|_| op.is_arith());
+                let (lv, rv) = if l.is_untyped() && !r.is_untyped() {
+                    let rv = self.eval(r, hint)?;
+                    (self.eval(l, Some(&rv))?, rv)
+                } else {
+                    let lv = self.eval(l, hint)?;
+                    let rv = self.eval(r, Some(&lv))?;
+                    (lv, rv)
+                };
+                value::binary(*op, &lv, &rv, e.line).map_or_else(|r| self.fail(e, &lv, r), Some)
+            }
+            ExprT::Cast(x, ty) => Some(value::cast(&self.eval(x, None)?, ty)),
+            ExprT::If(c, then, els) => {
+                let c = value::as_bool(&self.eval(c, None)?, e.line).ok()?;
+                // an `if` without `else` has no value, reported by the type checker
+                let body = if c { then } else { els.as_ref()? };
+                match (body.len(), tail(body)) {
+                    (1, Some(v)) => self.eval(v, hint),
+                    _ => {
+                        self.err(e, "only branches holding a single value are evaluated at compile time".into());
+                        None
+                    }
+                }
+            }
+            ExprT::Call(path, _) => {
+                self.err(e, format!("`{}` is a function call, functions only run with the program", path.join(".")));
+                None
+            }
+        }
+    }
+}
+
+///fold the values of the consts and globals in `ast`, names are bound by `binds`
+///
+/// returns the consts and the initial values of the globals by name
+pub fn fold(ast: &[NodeT], binds: &Bindings) -> (HashMap<String, VarT>, HashMap<String, VarT>, Vec<PErr>) {
+    let mut f = Folder { ast, binds, done: HashMap::new(), busy: Vec::new(), errs: Vec::new() };
+    let (mut consts, mut globals) = (HashMap::new(), HashMap::new());
+    for (i, node) in ast.iter().enumerate() {
+        let (NodeT::ConstNode(s) | NodeT::GlobalNode(s)) = node else { continue };
+        let Some(v) = f.node(i) else { continue };
+        // a name defined twice keeps its first value, the duplicate is already reported
+        let into = if matches!(node, NodeT::ConstNode(_)) { &mut consts } else { &mut globals };
+        into.entry(s.var.name.clone()).or_insert(v);
+    }
+    (consts, globals, f.errs)
+}
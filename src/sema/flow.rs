@@ -0,0 +1,251 @@
+use crate::{
+    ast::defs::{ExprT, FUNC, Jump, JumpT, LoopT, NodeT, VarT},
+    err::def::{ErrT, PErr},
+};
+
+///how control leaves a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exit {
+    ///`ret`
+    Ret,
+    ///continues in one of these blocks
+    Goto(Vec<usize>),
+    ///falls off the end of the body, its value is the last statement if that is an expression
+    End,
+}
+
+///a run of statements control enters at the top and leaves at the bottom
+#[derive(Debug)]
+pub struct Block<'a> {
+    pub stmts: Vec<&'a NodeT>,
+    pub exit: Exit,
+    ///the block whose `ret`, `break`, `continue` or endless loop this one follows, and why that makes it dead
+    pub after: Option<(usize, &'static str)>,
+}
+
+///control-flow graph of a fn body, entered at block 0
+#[derive(Debug)]
+pub struct Cfg<'a> {
+    pub blocks: Vec<Block<'a>>,
+    ///`break` and `continue` outside of any loop
+    pub stray: Vec<&'a Jump>,
+}
+
+struct Builder<'a> {
+    cfg: Cfg<'a>,
+    ///`continue` and `break` targets of the loops around the current block
+    loops: Vec<(usize, usize)>,
+}
+
+impl<'a> Builder<'a> {
+    fn fresh(&mut self, after: Option<(usize, &'static str)>) -> usize {
+        self.cfg.blocks.push(Block { stmts: Vec::new(), exit: Exit::End, after });
+        self.cfg.blocks.len() - 1
+    }
+
+    fn exit(&mut self, b: usize, exit: Exit) {
+        self.cfg.blocks[b].exit = exit;
+    }
+
+    ///add `body` from block `cur` on, the block control continues in after it
+    ///
+    /// `tail` is set for the statements that make the value of the fn, an
+    /// `if` there leaves every branch on its own instead of joining them
+    fn seq(&mut self, body: &'a [NodeT], mut cur: usize, tail: bool) -> usize {
+        for (i, node) in body.iter().enumerate() {
+            let last = tail && i + 1 == body.len();
+            self.cfg.blocks[cur].stmts.push(node);
+            match node {
+                NodeT::RetNode(_) => {
+                    self.exit(cur, Exit::Ret);
+                    cur = self.fresh(Some((cur, "it comes after a `ret`, so no path reaches it")));
+                }
+                NodeT::JumpNode(j) => {
+                    let (to, why) = match (self.loops.last(), j.kind) {
+                        (Some(&(head, _)), JumpT::Continue) => (head, "it comes after a `continue`, so no path reaches it"),
+                        (Some(&(_, after)), JumpT::Break) => (after, "it comes after a `break`, so no path reaches it"),
+                        // reported on its own, the code after it is not dead because of it
+                        (None, _) => {
+                            self.cfg.stray.push(j);
+                            continue;
+                        }
+                    };
+                    self.exit(cur, Exit::Goto(vec![to]));
+                    cur = self.fresh(Some((cur, why)));
+                }
+                NodeT::ExprNode(e) => {
+                    let ExprT::If(_, then, els) = &e.kind else { continue };
+                    let t = self.fresh(None);
+                    let join = self.fresh(Some((cur, "every branch of the `if` before it leaves, so no path reaches it")));
+                    let mut next = vec![t];
+                    let end = self.seq(then, t, last);
+                    if !last {
+                        self.exit(end, Exit::Goto(vec![join]));
+                    }
+                    match els {
+                        Some(els) => {
+                            let f = self.fresh(None);
+                            next.push(f);
+                            let end = self.seq(els, f, last);
+                            if !last {
+                                self.exit(end, Exit::Goto(vec![join]));
+                            }
+                        }
+                        None => next.push(join),
+                    }
+                    self.exit(cur, Exit::Goto(next));
+                    cur = join;
+                }
+                NodeT::LoopNode(l) => {
+                    // `head` checks the condition, `after` is where the loop is left
+                    let head = self.fresh(None);
+                    let body = self.fresh(None);
+                    let endless = matches!(l.kind, LoopT::Forever);
+                    let after = self.fresh(endless.then_some((head, "it comes after a `loop` without `break`, so no path reaches it")));
+                    self.exit(cur, Exit::Goto(vec![head]));
+                    self.exit(head, Exit::Goto(if endless { vec![body] } else { vec![body, after] }));
+                    self.loops.push((head, after));
+                    let end = self.seq(&l.body, body, false);
+                    self.exit(end, Exit::Goto(vec![head]));
+                    self.loops.pop();
+                    cur = after;
+                }
+                NodeT::LetNode(_) | NodeT::AssignNode(_) | NodeT::FUNCNode(_) | NodeT::ImportNode(_) | NodeT::ConstNode(_) | NodeT::GlobalNode(_) => {}
+            }
+        }
+        cur
+    }
+}
+
+impl<'a> Cfg<'a> {
+    pub fn build(body: &'a [NodeT]) -> Self {
+        let mut b = Builder { cfg: Cfg { blocks: Vec::new(), stray: Vec::new() }, loops: Vec::new() };
+        let entry = b.fresh(None);
+        b.seq(body, entry, true);
+        b.cfg
+    }
+
+    ///which blocks some path from the entry reaches
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut seen = vec![false; self.blocks.len()];
+        let mut todo = vec![0];
+        while let Some(b) = todo.pop() {
+            if std::mem::replace(&mut seen[b], true) {
+                continue;
+            }
+            if let Exit::Goto(next) = &self.blocks[b].exit {
+                todo.extend(next);
+            }
+        }
+        seen
+    }
+}
+
+///span of a statement
+fn span(node: &NodeT) -> Option<(usize, usize, usize)> {
+    match node {
+        NodeT::RetNode(r) => Some((r.line, r.start, r.end)),
+        NodeT::ExprNode(e) => Some((e.line, e.start, e.end)),
+        NodeT::LoopNode(l) => Some((l.line, l.start, l.end)),
+        NodeT::JumpNode(j) => Some((j.line, j.start, j.end)),
+        NodeT::LetNode(l) => Some((l.line, l.start, l.end)),
+        NodeT::AssignNode(a) => Some((a.line, a.start, a.end)),
+        NodeT::FUNCNode(_) | NodeT::ImportNode(_) | NodeT::ConstNode(_) | NodeT::GlobalNode(_) => None,
+    }
+}
+
+fn func(f: &FUNC, errs: &mut Vec<PErr>) {
+    let cfg = Cfg::build(&f.body);
+    for j in &cfg.stray {
+        let help = format!("`{}` only works inside `while`, `loop` or `for`", j.kind.keyword());
+        errs.push(PErr { et: ErrT::OutsideLoop, line: j.line, start: j.start, end: j.end, help: Some(help) });
+    }
+    let reachable = cfg.reachable();
+    for (b, &live) in cfg.blocks.iter().zip(&reachable) {
+        if !live {
+            // only the first statement of a dead stretch, not everything nested in it
+            if let Some((from, why)) = b.after && reachable[from]
+                && let Some((line, start, end)) = b.stmts.first().and_then(|s| span(s))
+            {
+                errs.push(PErr { et: ErrT::Unreachable, line, start, end, help: Some(why.into()) });
+            }
+            continue;
+        }
+        let value = matches!(b.stmts.last(), Some(NodeT::ExprNode(_)));
+        if b.exit == Exit::End && !value && !matches!(f.ret.TT, VarT::Nil) {
+            let help = format!("`{}` can reach its end without a value of type `{}`, add a `ret` or a final expression", f.name, f.ret.TT.name());
+            errs.push(PErr { et: ErrT::RetTNF, line: f.line, start: f.start, end: f.end, help: Some(help) });
+        }
+    }
+}
+
+///paths that end without a value and statements no path reaches, for every fn of `ast`
+pub fn check(ast: &[NodeT]) -> Vec<PErr> {
+    let mut errs = Vec::new();
+    for node in ast {
+        if let NodeT::FUNCNode(f) = node {
+            func(f, &mut errs);
+        }
+    }
+    errs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{err::def::{ErrT, PErr}, sema::tests::diagnose};
+
+    fn kinds(errs: &[PErr]) -> Vec<&ErrT> {
+        errs.iter().map(|e| &e.et).collect()
+    }
+
+    #[test]
+    fn missing_ret() {
+        let errs = diagnose("fn f(i32 n) @ i32 { }");
+        assert!(matches!(kinds(&errs)[..], [ErrT::RetTNF]), "{:?}", errs);
+        assert!(diagnose("fn f(i32 n) @ i32 { n + 1 }").is_empty());
+        assert!(diagnose("fn f(i32 n) @ i32 { ret n; }").is_empty());
+    }
+
+    #[test]
+    fn code_after_ret() {
+        let errs = diagnose("fn f(i32 n) @ i32 { ret n; n + 1 }");
+        assert!(matches!(kinds(&errs)[..], [ErrT::Unreachable]), "{:?}", errs);
+        assert!(errs[0].et.is_warning());
+    }
+
+    #[test]
+    fn tail_if_without_else() {
+        let errs = diagnose("fn f(bool c) @ i32 { if c { ret 1; } }");
+        assert!(matches!(kinds(&errs)[..], [ErrT::RetTNF]), "{:?}", errs);
+    }
+
+    #[test]
+    fn endless_loop_needs_no_ret() {
+        assert!(diagnose("fn f() @ i32 { loop { } }").is_empty());
+        let errs = diagnose("fn f() @ i32 { loop { } ret 1; }");
+        assert!(matches!(kinds(&errs)[..], [ErrT::Unreachable]), "{:?}", errs);
+        assert!(errs[0].et.is_warning());
+    }
+
+    #[test]
+    fn code_after_break() {
+        let errs = diagnose("fn f() @ i32 { let mut n: i32 = 0; while true { break; n += 1; } n }");
+        assert!(matches!(kinds(&errs)[..], [ErrT::Unreachable]), "{:?}", errs);
+    }
+
+    #[test]
+    fn stray_break() {
+        let errs = diagnose("fn f() @ i32 { break; 1 }");
+        assert!(matches!(kinds(&errs)[..], [ErrT::OutsideLoop, ..]), "{:?}", errs);
+        let errs = diagnose("fn f() @ i32 { continue; }");
+        assert!(kinds(&errs).iter().any(|e| matches!(e, ErrT::OutsideLoop)), "{:?}", errs);
+    }
+
+    #[test]
+    fn nested_tail_ifs_return_everywhere() {
+        let src = "fn f(i32 n) @ i32 {\n    if n < 0 { ret 0; } else if n < 10 { if n == 5 { ret 5; } else { n } } else { ret 10; }\n}";
+        assert!(diagnose(src).is_empty(), "{:?}", diagnose(src));
+        let src = "fn f(i32 n) @ i32 {\n    if n < 0 { ret 0; } else if n < 10 { if n == 5 { ret 5; } } else { ret 10; }\n}";
+        assert!(kinds(&diagnose(src)).iter().any(|e| matches!(e, ErrT::RetTNF)));
+    }
+}
@@ -0,0 +1,320 @@
+pub mod consts;
+pub mod flow;
+pub mod scope;
+pub mod types;
+
+use std::collections::HashMap;
+
+use scope::Scopes;
+
+use crate::{
+    ast::defs::{Assign, Expr, ExprT, FUNC, LoopT, NodeT, VarT},
+    err::{def::{ErrT, PErr}, suggest::{did_you_mean, suggest}},
+    module::resolve::{FnRef, Lookup, Resolver},
+    token::lexer::{KEYWORDS, TYPE_NAMES},
+};
+
+///what a name refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decl {
+    ///a param or local, by the span start of its declaration
+    Local(usize),
+    Fn(FnRef),
+    ///a `const` of the same module, by its node index
+    Const(usize),
+    ///a `global` of the same module, by its node index
+    Global(usize),
+    ///provided by the host, e.g. a fn registered with an `Engine`
+    Extern,
+}
+
+///a fn the host provides, with its signature
+#[derive(Debug, Clone)]
+pub struct Extern {
+    pub name: String,
+    pub params: Vec<VarT>,
+    pub ret: VarT,
+}
+
+///every name used in a module bound to its declaration
+#[derive(Debug, Default, Clone)]
+pub struct Bindings {
+    ///keyed by the span start of the use
+    pub uses: HashMap<usize, Decl>,
+    ///values of the module's consts, folded at compile time
+    pub consts: HashMap<String, VarT>,
+    ///initial values of the module's globals
+    pub globals: HashMap<String, VarT>,
+}
+
+struct Pass<'a> {
+    resolver: &'a Resolver,
+    module: usize,
+    externs: &'a [Extern],
+    scopes: Scopes,
+    ///`let` locals by the span start of their name, whether they are `mut`
+    lets: HashMap<usize, bool>,
+    ///consts and globals of the module by name, private to it
+    statics: HashMap<String, Decl>,
+    binds: Bindings,
+    errs: Vec<PErr>,
+}
+
+fn err(et: ErrT, line: usize, start: usize, end: usize, help: Option<String>) -> PErr {
+    PErr { et, line, start, end, help }
+}
+
+impl Pass<'_> {
+    fn func(&mut self, f: &FUNC) {
+        self.scopes.push();
+        for a in f.args.iter().flatten() {
+            if self.scopes.declare(&a.name, a.start).is_some() {
+                let help = format!("`{}` is already a parameter of `{}`", a.name, f.name);
+                self.errs.push(err(ErrT::DupParam, a.line, a.start, a.end, Some(help)));
+            }
+        }
+        for node in &f.body {
+            self.node(node);
+        }
+        self.scopes.pop();
+    }
+
+    fn node(&mut self, node: &NodeT) {
+        match node {
+            NodeT::RetNode(r) => {
+                if let Some(v) = &r.value {
+                    self.expr(v);
+                }
+            }
+            NodeT::ExprNode(e) => self.expr(e),
+            NodeT::LoopNode(l) => {
+                self.scopes.push();
+                match &l.kind {
+                    LoopT::While(c) => self.expr(c),
+                    LoopT::Forever => {}
+                    LoopT::For { var, from, to } => {
+                        // the bounds are evaluated outside the loop
+                        self.expr(from);
+                        self.expr(to);
+                        self.scopes.declare(&var.name, var.start);
+                    }
+                }
+                self.block(&l.body);
+                self.scopes.pop();
+            }
+            NodeT::LetNode(l) => {
+                // the value cannot see the name it initializes
+                self.expr(&l.value);
+                self.scopes.shadow(&l.var.name, l.var.start);
+                self.lets.insert(l.var.start, l.var.dyna);
+            }
+            NodeT::AssignNode(a) => {
+                self.expr(&a.value);
+                self.expr(&a.target);
+                self.assign(a);
+            }
+            NodeT::JumpNode(_) | NodeT::FUNCNode(_) | NodeT::ImportNode(_) | NodeT::ConstNode(_) | NodeT::GlobalNode(_) => {}
+        }
+    }
+
+    ///only `let mut` locals and globals can be assigned to
+    fn assign(&mut self, a: &Assign) {
+        let t = &a.target;
+        let help = match self.binds.uses.get(&t.start) {
+            Some(Decl::Local(d)) => match self.lets.get(d) {
+                Some(true) => return,
+                Some(false) => format!("`{}` is not `mut`, declare it with `let mut` to change it", t),
+                None => format!("`{}` is a parameter or loop variable, copy it into a `let mut` to change it", t),
+            },
+            Some(Decl::Global(_)) => return,
+            Some(Decl::Const(_)) => format!("`{}` is a const, make it a `global` to change it", t),
+            Some(Decl::Fn(_) | Decl::Extern) => format!("`{}` is a function", t),
+            // not declared, already reported
+            None => return,
+        };
+        self.errs.push(err(ErrT::Immutable, t.line, t.start, t.end, Some(help)));
+    }
+
+    ///statements of a nested block, names declared inside end with it
+    fn block(&mut self, body: &[NodeT]) {
+        self.scopes.push();
+        for node in body {
+            self.node(node);
+        }
+        self.scopes.pop();
+    }
+
+    fn expr(&mut self, e: &Expr) {
+        match &e.kind {
+            ExprT::Num(_) | ExprT::Bool(_) => {}
+            ExprT::Ident(name) => self.bind(e, &[name.as_str()]),
+            ExprT::Unary(_, x) | ExprT::Cast(x, _) => self.expr(x),
+            ExprT::Binary(_, l, r) => {
+                self.expr(l);
+                self.expr(r);
+            }
+            ExprT::Call(path, args) => {
+                let path: Vec<&str> = path.iter().map(String::as_str).collect();
+                self.bind(e, &path);
+                for a in args {
+                    self.expr(a);
+                }
+            }
+            ExprT::If(c, then, els) => {
+                self.expr(c);
+                self.block(then);
+                if let Some(els) = els {
+                    self.block(els);
+                }
+            }
+        }
+    }
+
+    ///bind the name `path` used at `e`: locals, then consts and globals, then fns, then host names
+    fn bind(&mut self, e: &Expr, path: &[&str]) {
+        if let [name] = path && let Some(d) = self.scopes.lookup(name) {
+            self.binds.uses.insert(e.start, Decl::Local(d));
+            return;
+        }
+        if let [name] = path && let Some(&d) = self.statics.get(*name) {
+            self.binds.uses.insert(e.start, d);
+            return;
+        }
+        let lookup = self.resolver.lookup(self.module, path);
+        let (et, help) = match lookup {
+            Lookup::Found(r) => {
+                self.binds.uses.insert(e.start, Decl::Fn(r));
+                return;
+            }
+            _ if path.len() == 1 && self.externs.iter().any(|x| x.name == path[0]) => {
+                self.binds.uses.insert(e.start, Decl::Extern);
+                return;
+            }
+            Lookup::Private(_) => (ErrT::UndefName, Some(format!("`{}` exists but is not `pub`", path.join(".")))),
+            Lookup::Ambiguous(_) => (ErrT::UndefName, Some(format!("more than one imported module has a `pub fn {}`, qualify it with the module name", path[path.len() - 1]))),
+            Lookup::NotFound => self.undefined(path),
+        };
+        // a call spans its arguments too, point at the name only
+        let end = e.start + path.join(".").len();
+        self.errs.push(err(et, e.line, e.start, end, help));
+    }
+
+    ///kind and help for a name nothing declares, a near keyword or type makes it a typo
+    fn undefined(&self, path: &[&str]) -> (ErrT, Option<String>) {
+        let [word] = path else { return (ErrT::UndefName, None) };
+        let ns = &self.resolver.spaces[self.module];
+        let fns = ns.locals.keys().map(String::as_str)
+            .chain(ns.imports.iter().flat_map(|&(_, t)| self.resolver.spaces[t].exports.keys().map(String::as_str)));
+        let names: Vec<&str> = self.scopes.names().chain(self.statics.keys().map(String::as_str)).chain(fns).chain(self.externs.iter().map(|x| x.name.as_str())).collect();
+        match suggest(word, KEYWORDS.iter().chain(TYPE_NAMES).copied().chain(names.iter().copied())) {
+            Some(s) if !names.contains(&s.as_str()) => (ErrT::UnknownIdent, Some(did_you_mean(&s))),
+            s => (ErrT::UndefName, s.map(|s| did_you_mean(&s))),
+        }
+    }
+}
+
+///resolve every name used in `ast`, module `module` of `resolver`
+///
+/// also reports names defined twice in the module and folds the values of
+/// its consts and globals. `externs` are names the host provides on top of
+/// the source.
+pub fn resolve(resolver: &Resolver, module: usize, ast: &[NodeT], externs: &[Extern]) -> (Bindings, Vec<PErr>) {
+    let mut pass = Pass {
+        resolver,
+        module,
+        externs,
+        scopes: Scopes::default(),
+        lets: HashMap::new(),
+        statics: HashMap::new(),
+        binds: Bindings::default(),
+        errs: Vec::new(),
+    };
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for (i, node) in ast.iter().enumerate() {
+        let (name, line, start, end, decl) = match node {
+            NodeT::FUNCNode(f) => (&f.name, f.line, f.start, f.end, None),
+            NodeT::ConstNode(s) => (&s.var.name, s.var.line, s.var.start, s.var.end, Some(Decl::Const(i))),
+            NodeT::GlobalNode(s) => (&s.var.name, s.var.line, s.var.start, s.var.end, Some(Decl::Global(i))),
+            _ => continue,
+        };
+        if let Some(first) = seen.insert(name, line) {
+            seen.insert(name, first);
+            let help = format!("`{}` is already defined on line {}", name, first);
+            pass.errs.push(err(ErrT::DupFunc, line, start, end, Some(help)));
+        } else if let Some(d) = decl {
+            pass.statics.insert(name.clone(), d);
+        }
+    }
+    for node in ast {
+        match node {
+            NodeT::FUNCNode(f) => pass.func(f),
+            NodeT::ConstNode(s) | NodeT::GlobalNode(s) => pass.expr(&s.value),
+            _ => {}
+        }
+    }
+    let (consts, globals, errs) = consts::fold(ast, &pass.binds);
+    pass.binds.consts = consts;
+    pass.binds.globals = globals;
+    pass.errs.extend(errs);
+    (pass.binds, pass.errs)
+}
+
+///type and control-flow errors of module `module`, `asts` holds every module of the build
+pub fn check(asts: &[&[NodeT]], module: usize, binds: &Bindings, externs: &[Extern]) -> Vec<PErr> {
+    let mut errs = types::check(asts, module, binds, externs);
+    errs.extend(flow::check(asts[module]));
+    errs
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::module::graph::ModGraph;
+
+    ///resolve and check `src`, a single module that must parse cleanly
+    pub fn diagnose(src: &str) -> Vec<PErr> {
+        let p = crate::parse(src);
+        assert!(p.errs.is_empty(), "{:?}", p.errs);
+        let ast = p.get_ast();
+        let graph = ModGraph { names: vec![String::new()], edges: vec![Vec::new()] };
+        let resolver = Resolver::from_asts(&graph, std::slice::from_ref(ast));
+        let (binds, mut errs) = resolve(&resolver, 0, ast, &[]);
+        errs.extend(check(&[ast], 0, &binds, &[]));
+        errs
+    }
+
+    ///the help of the only diagnostic of `src`
+    pub fn only(src: &str) -> (ErrT, String) {
+        let mut errs = diagnose(src);
+        assert_eq!(errs.len(), 1, "{:?}", errs);
+        let e = errs.remove(0);
+        (e.et, e.help.unwrap_or_default())
+    }
+
+    #[test]
+    fn clean() {
+        assert!(diagnose("fn add(i32 a, i32 b) @ i32 { a + b }\nfn main() @ i32 { add(1, 2) * 2 }").is_empty());
+    }
+
+    #[test]
+    fn undefined_name_suggests() {
+        let (et, help) = only("fn main(i32 count) @ i32 { cuont }");
+        assert!(matches!(et, ErrT::UndefName), "{:?}", et);
+        assert!(help.contains("count"), "{}", help);
+    }
+
+    #[test]
+    fn duplicate_definitions() {
+        let (et, _) = only("fn f() @ i32 { 1 }\nfn f() @ i32 { 2 }");
+        assert!(matches!(et, ErrT::DupFunc), "{:?}", et);
+        let (et, _) = only("fn f() @ i32 { 1 }\nconst f: i32 = 2;");
+        assert!(matches!(et, ErrT::DupFunc), "{:?}", et);
+    }
+
+    #[test]
+    fn assign_needs_mut() {
+        let (et, help) = only("fn main() @ i32 { let n: i32 = 1; n = 2; n }");
+        assert!(matches!(et, ErrT::Immutable), "{:?}", et);
+        assert!(help.contains("let mut"), "{}", help);
+    }
+}
@@ -0,0 +1,44 @@
+///nested scopes of local names, innermost last
+///
+/// each name maps to the span start of its declaration
+#[derive(Debug, Default)]
+pub struct Scopes {
+    stack: Vec<Vec<(String, usize)>>,
+}
+
+impl Scopes {
+    pub fn push(&mut self) {
+        self.stack.push(Vec::new());
+    }
+
+    pub fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    ///declare `name` in the innermost scope, the earlier declaration if it already has one
+    pub fn declare(&mut self, name: &str, start: usize) -> Option<usize> {
+        let scope = self.stack.last_mut()?;
+        if let Some(&(_, prev)) = scope.iter().find(|(n, _)| n == name) {
+            return Some(prev);
+        }
+        scope.push((name.to_string(), start));
+        None
+    }
+
+    ///declare `name` in the innermost scope, hiding any earlier one of the same name
+    pub fn shadow(&mut self, name: &str, start: usize) {
+        if let Some(scope) = self.stack.last_mut() {
+            scope.push((name.to_string(), start));
+        }
+    }
+
+    ///the declaration `name` refers to, inner scopes shadow outer ones
+    pub fn lookup(&self, name: &str) -> Option<usize> {
+        self.stack.iter().rev().flat_map(|s| s.iter().rev()).find(|(n, _)| n == name).map(|&(_, d)| d)
+    }
+
+    ///every name in scope, for suggestions
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.stack.iter().flatten().map(|(n, _)| n.as_str())
+    }
+}
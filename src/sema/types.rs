@@ -0,0 +1,504 @@
+use crate::{
+    ast::defs::{BinOp, Expr, ExprT, FUNC, Loop, LoopT, NodeT, UnOp, VarT, tail},
+    err::def::{ErrT, PErr},
+};
+
+use super::{Bindings, Decl, Extern};
+
+///the type of an expression as far as it is known
+#[derive(Debug, Clone)]
+enum Ty {
+    Known(VarT),
+    ///an integer literal not tied to a type yet
+    IntLit,
+    ///a float literal not tied to a type yet
+    FloatLit,
+    ///an error was already reported for this expression
+    Unknown,
+    ///a block or `if` that does not end in a value
+    Unit,
+    ///control never gets past it, e.g. a block ending in `ret`
+    Never,
+}
+
+const NO_VALUE: &str = "this `if` has no value, it needs an `else` and a value at the end of every branch";
+
+struct Checker<'a> {
+    asts: &'a [&'a [NodeT]],
+    ///the module being checked
+    module: usize,
+    binds: &'a Bindings,
+    externs: &'a [Extern],
+    ///params and locals of the fn being checked, by the span start of their name
+    vars: Vec<(usize, VarT)>,
+    ///return type of the fn being checked
+    ret: VarT,
+    errs: Vec<PErr>,
+}
+
+fn fits(text: &str, ty: &VarT) -> bool {
+    match ty {
+        VarT::I32(_) => text.parse::<i32>().is_ok(),
+        VarT::I64(_) => text.parse::<i64>().is_ok(),
+        VarT::U8(_) => text.parse::<u8>().is_ok(),
+        VarT::F32(_) => text.parse::<f32>().is_ok(),
+        VarT::F64(_) => text.parse::<f64>().is_ok(),
+        VarT::Bool(_) | VarT::Nil => false,
+    }
+}
+
+impl Checker<'_> {
+    fn err(&mut self, et: ErrT, e: &Expr, help: String) {
+        self.errs.push(PErr { et, line: e.line, start: e.start, end: e.end, help: Some(help) });
+    }
+
+    fn func(&mut self, f: &FUNC) {
+        self.vars = f.args.iter().flatten().map(|a| (a.start, a.TT.clone())).collect();
+        self.ret = f.ret.TT.clone();
+        let ret = f.ret.TT.clone();
+        match ret {
+            // the value of the body, if any, is dropped
+            VarT::Nil => {
+                self.block(&f.body, None);
+            }
+            _ => self.expect_block(&f.body, &ret, None),
+        }
+    }
+
+    fn stmt(&mut self, node: &NodeT) {
+        match node {
+            NodeT::RetNode(r) => match (&r.value, self.ret.clone()) {
+                (Some(v), VarT::Nil) => {
+                    self.value(v, None);
+                    self.err(ErrT::RetTMisMatch("Return type mismatch"), v, "the function returns nothing, use a bare `ret`".into());
+                }
+                (Some(v), ret) => self.expect(v, &ret, ErrT::RetTMisMatch("Return type mismatch")),
+                (None, VarT::Nil) => {}
+                (None, ret) => {
+                    let help = format!("the function returns {}, give `ret` a value", ret.name());
+                    let et = ErrT::RetTMisMatch("`ret` without a value");
+                    self.errs.push(PErr { et, line: r.line, start: r.start, end: r.end, help: Some(help) });
+                }
+            },
+            NodeT::ExprNode(e) => {
+                self.infer(e, None);
+            }
+            NodeT::LoopNode(l) => self.r#loop(l),
+            NodeT::LetNode(l) => {
+                self.expect(&l.value, &l.var.TT, ErrT::TypeMismatch);
+                self.vars.push((l.var.start, l.var.TT.clone()));
+            }
+            // assigning to anything but a local or global is reported by name resolution
+            NodeT::AssignNode(a) => match self.binds.uses.get(&a.target.start) {
+                Some(Decl::Local(_) | Decl::Global(_)) if let Ty::Known(t) = self.value(&a.target, None) => self.expect(&a.value, &t, ErrT::TypeMismatch),
+                _ => {
+                    self.value(&a.value, None);
+                }
+            },
+            NodeT::JumpNode(_) | NodeT::FUNCNode(_) | NodeT::ImportNode(_) | NodeT::ConstNode(_) | NodeT::GlobalNode(_) => {}
+        }
+    }
+
+    ///type of the value of `body`, the last statement if it is an expression
+    fn block(&mut self, body: &[NodeT], expect: Option<&VarT>) -> Ty {
+        let Some((last, init)) = body.split_last() else { return Ty::Unit };
+        for node in init {
+            self.stmt(node);
+        }
+        match last {
+            NodeT::ExprNode(e) => self.infer(e, expect),
+            NodeT::RetNode(_) | NodeT::JumpNode(_) => {
+                self.stmt(last);
+                Ty::Never
+            }
+            _ => {
+                self.stmt(last);
+                Ty::Unit
+            }
+        }
+    }
+
+    ///check that the value of `body` has type `ty`
+    ///
+    /// `at` is the `if` the block belongs to, `None` for a fn body, whose
+    /// missing values are left to the flow check
+    fn expect_block(&mut self, body: &[NodeT], ty: &VarT, at: Option<&Expr>) {
+        let et = || ErrT::RetTMisMatch("Return type mismatch");
+        match (body.split_last(), at) {
+            (Some((NodeT::ExprNode(e), init)), _) => {
+                for node in init {
+                    self.stmt(node);
+                }
+                match at {
+                    None => self.expect_tail(e, ty, et()),
+                    Some(_) => self.expect(e, ty, ErrT::TypeMismatch),
+                }
+            }
+            (_, None) => {
+                self.block(body, None);
+            }
+            (_, Some(at)) => {
+                if let Ty::Unit = self.block(body, None) {
+                    self.err(ErrT::TypeMismatch, at, NO_VALUE.into());
+                }
+            }
+        }
+    }
+
+    ///`expect` for the value of a fn body, an `if` there may leave its missing values to the flow check
+    fn expect_tail(&mut self, e: &Expr, ty: &VarT, et: ErrT) {
+        match &e.kind {
+            ExprT::If(c, then, els) => {
+                self.cond(c);
+                match els {
+                    Some(els) => {
+                        self.expect_block(then, ty, None);
+                        self.expect_block(els, ty, None);
+                    }
+                    None => {
+                        self.block(then, None);
+                    }
+                }
+            }
+            _ => self.expect(e, ty, et),
+        }
+    }
+
+    ///check that `e` has type `ty`, reporting `et` if not
+    fn expect(&mut self, e: &Expr, ty: &VarT, et: ErrT) {
+        // every branch is held to `ty` on its own, so the error points at the branch
+        if let ExprT::If(c, then, Some(els)) = &e.kind {
+            self.cond(c);
+            self.expect_block(then, ty, Some(e));
+            self.expect_block(els, ty, Some(e));
+            return;
+        }
+        if let Ty::Known(t) = self.value(e, Some(ty)) && !t.same_type(ty) {
+            self.err(et, e, format!("expected {}, found {}", ty.name(), t.name()));
+        }
+    }
+
+    ///`infer` where a value is needed, a `Unit` is reported
+    fn value(&mut self, e: &Expr, expect: Option<&VarT>) -> Ty {
+        match self.infer(e, expect) {
+            Ty::Unit => {
+                let help = match &e.kind {
+                    ExprT::Call(path, _) => format!("`{}` returns nothing, so it has no value to use", path.join(".")),
+                    _ => NO_VALUE.into(),
+                };
+                self.err(ErrT::TypeMismatch, e, help);
+                Ty::Unknown
+            }
+            Ty::Never => Ty::Unknown,
+            t => t,
+        }
+    }
+
+    ///a condition of an `if` or `while`, which has to be a `bool`
+    fn cond(&mut self, c: &Expr) {
+        let found = match self.value(c, None) {
+            Ty::Known(VarT::Bool(_)) | Ty::Unknown | Ty::Unit | Ty::Never => return,
+            Ty::Known(t) => t.name(),
+            Ty::IntLit => "an integer",
+            Ty::FloatLit => "a float",
+        };
+        self.err(ErrT::TypeMismatch, c, format!("a condition must be bool, found {}; compare it instead, e.g. `{} != 0`", found, c));
+    }
+
+    fn r#loop(&mut self, l: &Loop) {
+        match &l.kind {
+            LoopT::While(c) => self.cond(c),
+            LoopT::Forever => {}
+            LoopT::For { var, from, to } => {
+                let ty = self.range(from, to);
+                self.vars.push((var.start, ty));
+            }
+        }
+        self.block(&l.body, None);
+    }
+
+    ///the type of the loop variable of `from..to`, the type of the bounds
+    fn range(&mut self, from: &Expr, to: &Expr) -> VarT {
+        let ty = match self.value(from, None) {
+            Ty::Known(t) => {
+                self.expect(to, &t, ErrT::TypeMismatch);
+                Some(t)
+            }
+            lit @ (Ty::IntLit | Ty::FloatLit) => match self.value(to, None) {
+                Ty::Known(t) => {
+                    self.value(from, Some(&t));
+                    Some(t)
+                }
+                Ty::IntLit if matches!(lit, Ty::IntLit) => Some(VarT::I32(0)),
+                Ty::IntLit | Ty::FloatLit => Some(VarT::F64(0.0)),
+                _ => None,
+            },
+            _ => {
+                self.value(to, None);
+                None
+            }
+        };
+        match ty {
+            Some(t @ (VarT::F32(_) | VarT::F64(_))) => {
+                let help = format!("a range counts in whole steps, its bounds cannot be {}", t.name());
+                self.errs.push(PErr { et: ErrT::TypeMismatch, line: from.line, start: from.start, end: to.end, help: Some(help) });
+                VarT::I32(0)
+            }
+            t => t.unwrap_or(VarT::I32(0)),
+        }
+    }
+
+    ///type of an `if`, branches agree on one type or it has none
+    fn r#if(&mut self, e: &Expr, c: &Expr, then: &[NodeT], els: Option<&[NodeT]>, expect: Option<&VarT>) -> Ty {
+        self.cond(c);
+        let Some(els) = els else {
+            self.block(then, None);
+            return Ty::Unit;
+        };
+        let a = self.block(then, expect);
+        let b = self.block(els, expect);
+        match (a, b) {
+            (Ty::Never, t) | (t, Ty::Never) => t,
+            (Ty::Unit, _) | (_, Ty::Unit) => Ty::Unit,
+            (Ty::Unknown, _) | (_, Ty::Unknown) => Ty::Unknown,
+            (Ty::Known(a), Ty::Known(b)) if !a.same_type(&b) => {
+                self.err(ErrT::TypeMismatch, e, format!("`if` and `else` have different types, {} and {}", a.name(), b.name()));
+                Ty::Unknown
+            }
+            (Ty::Known(t), Ty::Known(_)) => Ty::Known(t),
+            // a literal branch takes the type of the other one
+            (Ty::Known(t), _) => {
+                if let Some(x) = tail(els) {
+                    self.value(x, Some(&t));
+                }
+                Ty::Known(t)
+            }
+            (_, Ty::Known(t)) => {
+                if let Some(x) = tail(then) {
+                    self.value(x, Some(&t));
+                }
+                Ty::Known(t)
+            }
+            (Ty::IntLit, Ty::IntLit) => Ty::IntLit,
+            _ => Ty::FloatLit,
+        }
+    }
+
+    ///a literal checked against `expect`, untyped without one
+    fn literal(&mut self, e: &Expr, text: &str, expect: Option<&VarT>) -> Ty {
+        let float = text.contains('.');
+        match expect {
+            None if float => Ty::FloatLit,
+            None => Ty::IntLit,
+            Some(ty) if fits(text, ty) => Ty::Known(ty.clone()),
+            Some(ty) => {
+                self.err(ErrT::TypeMismatch, e, format!("`{}` is not a valid {}", text, ty.name()));
+                Ty::Unknown
+            }
+        }
+    }
+
+    ///infer the type of `e`, untyped literals take the type of `expect`
+    fn infer(&mut self, e: &Expr, expect: Option<&VarT>) -> Ty {
+        match &e.kind {
+            ExprT::Num(n) => self.literal(e, n, expect),
+            ExprT::Bool(_) => Ty::Known(VarT::Bool(false)),
+            ExprT::Ident(name) => match self.binds.uses.get(&e.start) {
+                Some(Decl::Local(d)) => match self.vars.iter().find(|(s, _)| s == d) {
+                    Some((_, t)) => Ty::Known(t.clone()),
+                    None => Ty::Unknown,
+                },
+                Some(&(Decl::Const(n) | Decl::Global(n))) => match &self.asts[self.module][n] {
+                    NodeT::ConstNode(s) | NodeT::GlobalNode(s) => Ty::Known(s.var.TT.clone()),
+                    _ => Ty::Unknown,
+                },
+                Some(Decl::Fn(_) | Decl::Extern) => {
+                    self.err(ErrT::TypeMismatch, e, format!("`{}` is a function, call it with `{}(...)`", name, name));
+                    Ty::Unknown
+                }
+                None => Ty::Unknown,
+            },
+            ExprT::Unary(UnOp::Neg, x) => {
+                if let ExprT::Num(n) = &x.kind {
+                    return self.literal(e, &format!("-{}", n), expect);
+                }
+                let t = self.value(x, expect);
+                match t {
+                    Ty::Known(VarT::U8(_)) => self.err(ErrT::TypeMismatch, e, "u8 cannot be negative, convert it with `as` first".into()),
+                    Ty::Known(VarT::Bool(_)) => self.err(ErrT::TypeMismatch, e, "`-` needs a number, found bool; use `!` to negate a bool".into()),
+                    _ => return t,
+                }
+                Ty::Unknown
+            }
+            ExprT::Unary(UnOp::Not, x) => {
+                self.expect(x, &VarT::Bool(false), ErrT::TypeMismatch);
+                Ty::Known(VarT::Bool(false))
+            }
+            ExprT::Binary(op, l, r) => self.binary(*op, e, l, r, expect),
+            ExprT::Cast(x, ty) => {
+                self.value(x, None);
+                Ty::Known(ty.clone())
+            }
+            ExprT::Call(path, args) => self.call(e, &path.join("."), args),
+            ExprT::If(c, then, els) => self.r#if(e, c, then, els.as_deref(), expect),
+        }
+    }
+
+    fn binary(&mut self, op: BinOp, e: &Expr, l: &Expr, r: &Expr, expect: Option<&VarT>) -> Ty {
+        if !op.is_arith() && !op.is_cmp() {
+            self.expect(l, &VarT::Bool(false), ErrT::TypeMismatch);
+            self.expect(r, &VarT::Bool(false), ErrT::TypeMismatch);
+            return Ty::Known(VarT::Bool(false));
+        }
+        // the operands of a comparison do not take the `bool` it produces
+        let t = self.operands(op, e, l, r, expect.filter(|_| op.is_arith()));
+        // a bool only has `==` and `!=`
+        if let Ty::Known(VarT::Bool(_)) = t && !matches!(op, BinOp::Eq | BinOp::Ne) {
+            self.err(ErrT::TypeMismatch, e, format!("`{}` needs numbers, found bool", op.symbol()));
+            return Ty::Unknown;
+        }
+        if op.is_cmp() { Ty::Known(VarT::Bool(false)) } else { t }
+    }
+
+    ///the type both operands of `op` agree on
+    fn operands(&mut self, op: BinOp, e: &Expr, l: &Expr, r: &Expr, expect: Option<&VarT>) -> Ty {
+        let lt = self.value(l, expect);
+        let ty = match lt {
+            Ty::Known(t) => t,
+            Ty::Unknown | Ty::Unit | Ty::Never => {
+                self.value(r, None);
+                return Ty::Unknown;
+            }
+            // a literal on the left takes its type from the right
+            Ty::IntLit | Ty::FloatLit => {
+                return match self.value(r, expect) {
+                    Ty::Known(t) => {
+                        self.value(l, Some(&t));
+                        Ty::Known(t)
+                    }
+                    Ty::FloatLit => Ty::FloatLit,
+                    Ty::IntLit => lt,
+                    _ => Ty::Unknown,
+                };
+            }
+        };
+        match self.value(r, Some(&ty)) {
+            Ty::Known(rt) if !rt.same_type(&ty) => {
+                let help = match (&ty, &rt) {
+                    (VarT::Bool(_), _) | (_, VarT::Bool(_)) if op.is_arith() => format!("`{}` needs numbers, found bool", op.symbol()),
+                    (VarT::Bool(_), _) | (_, VarT::Bool(_)) => format!("`{}` needs both sides of one type, found {} and {}", op.symbol(), ty.name(), rt.name()),
+                    _ => format!("`{}` mixes {} and {}, convert one side with `as`", op.symbol(), ty.name(), rt.name()),
+                };
+                self.err(ErrT::TypeMismatch, e, help);
+                Ty::Unknown
+            }
+            _ => Ty::Known(ty),
+        }
+    }
+
+    ///arity and argument types against the callee's params, the callee's return type
+    fn call(&mut self, e: &Expr, name: &str, args: &[Expr]) -> Ty {
+        let (params, ret): (Vec<VarT>, VarT) = match self.binds.uses.get(&e.start) {
+            Some(Decl::Fn(r)) => match &self.asts[r.module][r.node] {
+                NodeT::FUNCNode(f) => (f.args.iter().flatten().map(|a| a.TT.clone()).collect(), f.ret.TT.clone()),
+                _ => return Ty::Unknown,
+            },
+            Some(Decl::Extern) => match self.externs.iter().find(|x| x.name == name) {
+                Some(x) => (x.params.clone(), x.ret.clone()),
+                None => return Ty::Unknown,
+            },
+            Some(Decl::Local(_) | Decl::Const(_) | Decl::Global(_)) => {
+                self.err(ErrT::TypeMismatch, e, format!("`{}` is a value, not a function", name));
+                return Ty::Unknown;
+            }
+            None => return Ty::Unknown,
+        };
+        if params.len() != args.len() {
+            let help = format!("`{}` takes {} argument(s) but {} were given", name, params.len(), args.len());
+            self.err(ErrT::ArgCount, e, help);
+        }
+        for (a, p) in args.iter().zip(&params) {
+            self.expect(a, p, ErrT::TypeMismatch);
+        }
+        for a in args.iter().skip(params.len()) {
+            self.value(a, None);
+        }
+        match ret {
+            VarT::Nil => Ty::Unit,
+            ret => Ty::Known(ret),
+        }
+    }
+}
+
+///type check every fn, const and global of module `module`, `asts` holds every module of the build
+pub fn check(asts: &[&[NodeT]], module: usize, binds: &Bindings, externs: &[Extern]) -> Vec<PErr> {
+    let mut c = Checker { asts, module, binds, externs, vars: Vec::new(), ret: VarT::I32(0), errs: Vec::new() };
+    for node in asts[module] {
+        match node {
+            NodeT::FUNCNode(f) => c.func(f),
+            NodeT::ConstNode(s) | NodeT::GlobalNode(s) => c.expect(&s.value, &s.var.TT, ErrT::TypeMismatch),
+            _ => {}
+        }
+    }
+    c.errs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{err::def::ErrT, sema::tests::{diagnose, only}};
+
+    fn mismatch(src: &str) -> String {
+        let (et, help) = only(src);
+        assert!(matches!(et, ErrT::TypeMismatch), "{:?}: {}", et, help);
+        help
+    }
+
+    #[test]
+    fn arity() {
+        let (et, help) = only("fn add(i32 a, i32 b) @ i32 { a + b }\nfn main() @ i32 { add(1) }");
+        assert!(matches!(et, ErrT::ArgCount), "{:?}", et);
+        assert!(help.contains("takes 2"), "{}", help);
+    }
+
+    #[test]
+    fn numeric_types_do_not_mix() {
+        assert!(mismatch("fn f(i32 a, i64 b) @ i64 { a + b }").contains("`as`"));
+        assert!(diagnose("fn f(i32 a, i64 b) @ i64 { a as i64 + b }").is_empty());
+        // an untyped literal takes the type of the other side
+        assert!(diagnose("fn f(u8 a) @ u8 { a + 1 }").is_empty());
+    }
+
+    #[test]
+    fn ret_against_the_return_type() {
+        let (et, help) = only("fn f(i64 x) @ i32 { ret x; }");
+        assert!(matches!(et, ErrT::RetTMisMatch(_)), "{:?}", et);
+        assert!(help.contains("i32"), "{}", help);
+        assert!(diagnose("fn f(i64 x) @ i32 { ret x as i32; }").is_empty());
+        assert!(!diagnose("fn f() { ret 1; }").is_empty());
+        assert!(!diagnose("fn f() @ i32 { ret; }").is_empty());
+    }
+
+    #[test]
+    fn literal_range() {
+        assert!(mismatch("fn f() @ u8 { 300 }").contains("not a valid u8"));
+        assert!(diagnose("fn f() @ u8 { 255 }").is_empty());
+        assert!(mismatch("fn f() @ i32 { 1.5 }").contains("not a valid i32"));
+    }
+
+    #[test]
+    fn conditions_are_bool() {
+        assert!(mismatch("fn f(i32 a) @ i32 { if a { 1 } else { 2 } }").contains("must be bool"));
+        assert!(mismatch("fn f() @ i32 { while 1 { } 0 }").contains("must be bool"));
+        assert!(mismatch("fn f(bool a) @ bool { !1 }").contains("bool"));
+        assert!(diagnose("fn f(i32 a, bool b) @ bool { a > 0 && !b || a == 2 }").is_empty());
+    }
+
+    #[test]
+    fn if_branches() {
+        assert!(diagnose("fn f(bool c) @ i32 { if c { 1 } else { 2 } }").is_empty());
+        let (et, _) = only("fn f(bool c) @ i32 { if c { 1 } else { true } }");
+        assert!(matches!(et, ErrT::RetTMisMatch(_)), "{:?}", et);
+        assert!(mismatch("fn f(bool c) @ i32 { let x: i32 = if c { 1 }; x }").contains("needs an `else`"));
+        assert!(!diagnose("fn f(bool c) @ i32 { let x: i32 = if c { 1 } else { 2 as i64 }; x }").is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use std::{
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+///when to emit ANSI colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    ///color only when stdout is a terminal, honoring `NO_COLOR` / `CLICOLOR_FORCE`
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn from_arg(s: &str) -> Option<Self> {
+        match s {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+}
+
+///box-drawing pieces used by diagnostics and help output
+pub struct Glyphs {
+    pub top: &'static str,
+    pub bot: &'static str,
+    pub bar: &'static str,
+    pub tee: &'static str,
+    pub end: &'static str,
+    pub line: &'static str,
+}
+
+const UNICODE: Glyphs = Glyphs { top: "╭", bot: "╰", bar: "│", tee: "├─", end: "└─", line: "─" };
+const ASCII: Glyphs = Glyphs { top: "+", bot: "+", bar: "|", tee: "|-", end: "`-", line: "-" };
+
+static USE_ASCII: AtomicBool = AtomicBool::new(false);
+
+///set the global color mode and glyph set, call once before printing anything
+pub fn init(mode: ColorMode, ascii: bool) {
+    colored::control::set_override(use_color(mode));
+    USE_ASCII.store(ascii, Ordering::Relaxed);
+}
+
+fn use_color(mode: ColorMode) -> bool {
+    match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => {
+            let set = |k: &str| std::env::var_os(k).is_some_and(|v| !v.is_empty());
+            if set("NO_COLOR") {
+                false
+            } else if set("CLICOLOR_FORCE") && std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+                true
+            } else {
+                std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+///the glyph set picked by `init`
+#[inline(always)]
+pub fn glyphs() -> &'static Glyphs {
+    if USE_ASCII.load(Ordering::Relaxed) { &ASCII } else { &UNICODE }
+}
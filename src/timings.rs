@@ -0,0 +1,175 @@
+use std::time::{Duration, Instant};
+
+use crate::json::Json;
+
+///compiler phases that can be timed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    ///opening, mapping and hashing the file
+    Read,
+    ///loading the ast from the incremental cache
+    Cache,
+    Lex,
+    Parse,
+    ///collecting what a module declares and exports
+    Namespace,
+    ///binding every name in a module to its declaration
+    Resolve,
+    Typecheck,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Read => "read",
+            Phase::Cache => "cache",
+            Phase::Lex => "lex",
+            Phase::Parse => "parse",
+            Phase::Namespace => "namespace",
+            Phase::Resolve => "resolve",
+            Phase::Typecheck => "typecheck",
+        }
+    }
+}
+
+///how `--timings` should report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimingsMode {
+    Off,
+    Text,
+    Json,
+}
+
+impl TimingsMode {
+    pub fn from_arg(s: &str) -> Option<Self> {
+        match s {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+///per-phase durations of a single file, in the order the phases ran
+#[derive(Debug, Clone, Default)]
+pub struct FileTimings {
+    pub file: String,
+    pub phases: Vec<(Phase, Duration)>,
+}
+
+impl FileTimings {
+    pub fn new(file: impl Into<String>) -> Self {
+        Self { file: file.into(), phases: Vec::new() }
+    }
+
+    ///run `f` and record how long it took under `phase`
+    #[inline(always)]
+    pub fn time<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let out = f();
+        self.phases.push((phase, start.elapsed()));
+        out
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, d)| *d).sum()
+    }
+}
+
+fn micros(d: Duration) -> Json {
+    Json::Num(d.as_nanos() as f64 / 1000.0)
+}
+
+///timings as a JSON document, durations are in microseconds
+pub fn to_json(files: &[FileTimings]) -> Json {
+    let total: Duration = files.iter().map(FileTimings::total).sum();
+    Json::obj([
+        ("version", Json::str(env!("CARGO_PKG_VERSION"))),
+        ("unit", Json::str("us")),
+        ("files", Json::Arr(files.iter().map(|f| Json::obj([
+            ("file", Json::str(f.file.clone())),
+            ("phases", Json::Arr(f.phases.iter().map(|(p, d)| Json::obj([
+                ("phase", Json::str(p.name())),
+                ("time", micros(*d)),
+            ])).collect())),
+            ("total", micros(f.total())),
+        ])).collect())),
+        ("total", micros(total)),
+    ])
+}
+
+///timings as a table per file, one phase per line
+pub fn to_text(files: &[FileTimings]) -> String {
+    let mut out = String::new();
+    for f in files {
+        out += &format!("timings for {}:\n", f.file);
+        for (p, d) in &f.phases {
+            out += &format!("  {:<10} {:>10.2?}\n", p.name(), d);
+        }
+        out += &format!("  {:<10} {:>10.2?}\n", "total", f.total());
+    }
+    out
+}
+
+///print the timings to stderr so they never mix with diagnostics
+pub fn report(files: &[FileTimings], mode: TimingsMode) {
+    match mode {
+        TimingsMode::Off => {}
+        TimingsMode::Json => eprintln!("{}", to_json(files)),
+        TimingsMode::Text => eprint!("{}", to_text(files)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<FileTimings> {
+        let mut a = FileTimings::new("src/main.sip");
+        a.phases.push((Phase::Lex, Duration::from_micros(15)));
+        a.phases.push((Phase::Parse, Duration::from_micros(40)));
+        a.phases.push((Phase::Typecheck, Duration::from_micros(5)));
+        let mut b = FileTimings::new("src/util.sip");
+        b.phases.push((Phase::Cache, Duration::from_nanos(2500)));
+        vec![a, b]
+    }
+
+    #[test]
+    fn time_records_in_order() {
+        let mut t = FileTimings::new("a.sip");
+        assert_eq!(t.time(Phase::Lex, || 1) + t.time(Phase::Parse, || 2), 3);
+        let phases: Vec<Phase> = t.phases.iter().map(|(p, _)| *p).collect();
+        assert_eq!(phases, [Phase::Lex, Phase::Parse]);
+    }
+
+    #[test]
+    fn text_report() {
+        assert_eq!(to_text(&sample()), "\
+timings for src/main.sip:
+  lex           15.00µs
+  parse         40.00µs
+  typecheck      5.00µs
+  total         60.00µs
+timings for src/util.sip:
+  cache          2.50µs
+  total          2.50µs
+");
+        assert_eq!(to_text(&[]), "");
+    }
+
+    #[test]
+    fn json_report() {
+        let j = Json::parse(&to_json(&sample()).to_string()).unwrap();
+        assert_eq!(j.get("unit").and_then(Json::as_str), Some("us"));
+        assert_eq!(j.get("total").and_then(Json::as_f64), Some(62.5));
+        let files = j.get("files").and_then(Json::as_arr).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].get("file").and_then(Json::as_str), Some("src/main.sip"));
+        assert_eq!(files[0].get("total").and_then(Json::as_f64), Some(60.0));
+        let phases: Vec<(&str, f64)> = files[0].get("phases").and_then(Json::as_arr).unwrap().iter()
+            .map(|p| (p.get("phase").and_then(Json::as_str).unwrap(), p.get("time").and_then(Json::as_f64).unwrap()))
+            .collect();
+        assert_eq!(phases, [("lex", 15.0), ("parse", 40.0), ("typecheck", 5.0)]);
+        assert_eq!(files[1].get("total").and_then(Json::as_f64), Some(2.5));
+    }
+}
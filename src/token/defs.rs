@@ -30,6 +30,55 @@ pub enum TT {
     GLOBAL,
     SemiColon,
     Nil,
+    Import,
+    Pub,
+    Dot,
+    ///end of input, produced by the parser once every token is consumed
+    EOF,
+    ///`// ...` up to the end of the line, only produced by `lex_trivia`
+    Comment,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    ///`as`, numeric conversion
+    As,
+    ///`:` between a name and its type
+    Colon,
+    If,
+    Else,
+    While,
+    Loop,
+    For,
+    In,
+    Break,
+    Continue,
+    ///`..` of a range, `a..b`
+    DotDot,
+    Let,
+    Mut,
+    ///`+=`, `-=`, `*=`, `/=` or `%=`, the value holds which
+    OpEq,
+    Bool,
+    True,
+    False,
+    ///`==`
+    EqEq,
+    ///`!=`
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    ///`&&`
+    AndAnd,
+    ///`||`
+    OrOr,
+    ///`!`, logical not
+    Bang,
+    ///a `"` or `'` string missing its closing quote, it runs to the end of the input
+    Unterminated,
 }
 #[allow(non_snake_case)]
 #[derive(Debug, Clone,PartialEq, Eq)]
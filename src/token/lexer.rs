@@ -1,16 +1,18 @@
 use crate::token::defs::{Token, TT, IDType};
 
-#[allow(unsafe_op_in_unsafe_fn)]
-#[inline(always)]
-unsafe fn bytes_to_str_unchecked(bytes: &[u8]) -> &str {
-    std::str::from_utf8_unchecked(bytes)
-}
-
 #[inline(always)]
 fn is_alphanumeric(c: u8) -> bool {
     c.is_ascii_alphanumeric() || c == b'_'
 }
 
+///every keyword `keyword_tt` knows about, used for typo suggestions
+pub const KEYWORDS: &[&str] = &[
+    "fn", "ret", "const", "global", "nil", "import", "pub", "as",
+    "if", "else", "while", "loop", "for", "in", "break", "continue", "let", "mut", "true", "false",
+];
+///built-in type names
+pub const TYPE_NAMES: &[&str] = &["i32", "i64", "u8", "f32", "f64", "bool"];
+
 #[inline(always)]
 fn keyword_tt(s: &str) -> TT {
     match s {
@@ -24,12 +26,41 @@ fn keyword_tt(s: &str) -> TT {
         "f32" => TT::F32,
         "f64" => TT::F64,
         "nil" => TT::Nil,
+        "import" => TT::Import,
+        "pub" => TT::Pub,
+        "as" => TT::As,
+        "if" => TT::If,
+        "else" => TT::Else,
+        "while" => TT::While,
+        "loop" => TT::Loop,
+        "for" => TT::For,
+        "in" => TT::In,
+        "break" => TT::Break,
+        "continue" => TT::Continue,
+        "let" => TT::Let,
+        "mut" => TT::Mut,
+        "bool" => TT::Bool,
+        "true" => TT::True,
+        "false" => TT::False,
         _ => TT::Ident(IDType::Nil),
     }
 }
 
+///tokens of `code`, comments are dropped
+#[inline(always)]
+pub fn lex(code: &str) -> Vec<Token> {
+    lex_impl(code, false)
+}
+
+///tokens of `code` including `TT::Comment` trivia, for tools that rewrite source
+pub fn lex_trivia(code: &str) -> Vec<Token> {
+    lex_impl(code, true)
+}
+
 #[inline(always)]
-pub fn lex(code: &[u8]) -> Vec<Token> {
+fn lex_impl(src: &str, trivia: bool) -> Vec<Token> {
+    // tokens start and end on ascii bytes or at the end, so every slice of `src` below is on a char boundary
+    let code = src.as_bytes();
     let mut tokens = Vec::with_capacity(code.len() / 2); // heuristic pre-alloc
     let mut index = 0;
     let mut line = 1;
@@ -51,9 +82,51 @@ pub fn lex(code: &[u8]) -> Vec<Token> {
             b']' => { tokens.push(Token::new(TT::RBigB, line, index, index + 1)); index += 1; continue; }
             b',' => { tokens.push(Token::new(TT::Comma, line, index, index + 1)); index += 1; continue; }
             b';' => { tokens.push(Token::new(TT::SemiColon, line, index, index + 1)); index += 1; continue; }
+            b'=' | b'!' | b'<' | b'>' if code.get(index + 1) == Some(&b'=') => {
+                let tt = match c {
+                    b'=' => TT::EqEq,
+                    b'!' => TT::NotEq,
+                    b'<' => TT::LtEq,
+                    _ => TT::GtEq,
+                };
+                tokens.push(Token::new(tt, line, index, index + 2));
+                index += 2;
+                continue;
+            }
             b'=' => { tokens.push(Token::new(TT::Eqs, line, index, index + 1)); index += 1; continue; }
+            b'!' => { tokens.push(Token::new(TT::Bang, line, index, index + 1)); index += 1; continue; }
+            b'<' => { tokens.push(Token::new(TT::Lt, line, index, index + 1)); index += 1; continue; }
+            b'>' => { tokens.push(Token::new(TT::Gt, line, index, index + 1)); index += 1; continue; }
+            b'&' if code.get(index + 1) == Some(&b'&') => { tokens.push(Token::new(TT::AndAnd, line, index, index + 2)); index += 2; continue; }
+            b'|' if code.get(index + 1) == Some(&b'|') => { tokens.push(Token::new(TT::OrOr, line, index, index + 2)); index += 2; continue; }
+            b'.' if code.get(index + 1) == Some(&b'.') => { tokens.push(Token::new(TT::DotDot, line, index, index + 2)); index += 2; continue; }
+            b'.' => { tokens.push(Token::new(TT::Dot, line, index, index + 1)); index += 1; continue; }
+            b':' => { tokens.push(Token::new(TT::Colon, line, index, index + 1)); index += 1; continue; }
             b'@' => { tokens.push(Token::new(TT::At, line, index, index + 1)); index += 1; continue; }
             b'_' => { tokens.push(Token::new(TT::Underscore, line, index, index + 1)); index += 1; continue; }
+            b'+' | b'-' | b'*' | b'/' | b'%' if code.get(index + 1) == Some(&b'=') => {
+                let text = src[index..index + 2].to_string();
+                tokens.push(Token { TT: TT::OpEq, line, start: index, end: index + 2, value: Some(text) });
+                index += 2;
+                continue;
+            }
+            b'+' => { tokens.push(Token::new(TT::Plus, line, index, index + 1)); index += 1; continue; }
+            b'-' => { tokens.push(Token::new(TT::Minus, line, index, index + 1)); index += 1; continue; }
+            b'*' => { tokens.push(Token::new(TT::Star, line, index, index + 1)); index += 1; continue; }
+            b'%' => { tokens.push(Token::new(TT::Percent, line, index, index + 1)); index += 1; continue; }
+
+            b'/' if code.get(index + 1) == Some(&b'/') => {
+                start = index;
+                while index < code.len() && code[index] != b'\n' {
+                    index += 1;
+                }
+                if trivia {
+                    let text = src[start..index].trim_end().to_string();
+                    tokens.push(Token { TT: TT::Comment, line, start, end: start + text.len(), value: Some(text) });
+                }
+                continue;
+            }
+            b'/' => { tokens.push(Token::new(TT::Slash, line, index, index + 1)); index += 1; continue; }
 
             b'\'' | b'"' => {
                 let quote = c;
@@ -62,15 +135,18 @@ pub fn lex(code: &[u8]) -> Vec<Token> {
                 while index < code.len() && code[index] != quote {
                     index += 1;
                 }
-                index += 1; // skip closing quote
-                let slice = &code[start..index];
-                let id_type = if quote == b'\'' { IDType::SQ } else { IDType::DQ };
+                let tt = if index < code.len() {
+                    index += 1; // skip closing quote
+                    TT::Ident(if quote == b'\'' { IDType::SQ } else { IDType::DQ })
+                } else {
+                    TT::Unterminated
+                };
                 tokens.push(Token {
-                    TT: TT::Ident(id_type),
+                    TT: tt,
                     line,
                     start,
                     end: index,
-                    value: Some(unsafe { bytes_to_str_unchecked(slice) }.to_string()),
+                    value: Some(src[start..index].to_string()),
                 });
                 continue;
             }
@@ -80,13 +156,19 @@ pub fn lex(code: &[u8]) -> Vec<Token> {
                 while index < code.len() && code[index].is_ascii_digit() {
                     index += 1;
                 }
-                let slice = &code[start..index];
+                // `1.5`, but not `1.` or `1.x`
+                if code.get(index) == Some(&b'.') && code.get(index + 1).is_some_and(u8::is_ascii_digit) {
+                    index += 1;
+                    while index < code.len() && code[index].is_ascii_digit() {
+                        index += 1;
+                    }
+                }
                 tokens.push(Token {
                     TT: TT::NUM,
                     line,
                     start,
                     end: index,
-                    value: Some(unsafe { bytes_to_str_unchecked(slice) }.to_string()),
+                    value: Some(src[start..index].to_string()),
                 });
                 continue;
             }
@@ -96,8 +178,7 @@ pub fn lex(code: &[u8]) -> Vec<Token> {
                 while index < code.len() && is_alphanumeric(code[index]) {
                     index += 1;
                 }
-                let slice = &code[start..index];
-                let s = unsafe { bytes_to_str_unchecked(slice) };
+                let s = &src[start..index];
                 let tt = keyword_tt(s);
                 tokens.push(Token {
                     TT: tt,
@@ -126,4 +207,42 @@ impl Token {
     pub fn new(TT: TT, line: usize, start: usize, end: usize) -> Self {
         Self { TT, line, start, end, value: None }
     }
+
+    ///source text of the token
+    pub fn text(&self) -> &str {
+        if let Some(v) = &self.value {
+            return v;
+        }
+        match self.TT {
+            TT::LSmallB => "(",
+            TT::RsmallB => ")",
+            TT::LCurlyB => "{",
+            TT::RCurlyB => "}",
+            TT::LBigB => "[",
+            TT::RBigB => "]",
+            TT::Comma => ",",
+            TT::SemiColon => ";",
+            TT::Eqs => "=",
+            TT::Dot => ".",
+            TT::At => "@",
+            TT::Colon => ":",
+            TT::DotDot => "..",
+            TT::Underscore => "_",
+            TT::Plus => "+",
+            TT::Minus => "-",
+            TT::Star => "*",
+            TT::Slash => "/",
+            TT::Percent => "%",
+            TT::EqEq => "==",
+            TT::NotEq => "!=",
+            TT::Lt => "<",
+            TT::LtEq => "<=",
+            TT::Gt => ">",
+            TT::GtEq => ">=",
+            TT::AndAnd => "&&",
+            TT::OrOr => "||",
+            TT::Bang => "!",
+            _ => "",
+        }
+    }
 }
@@ -0,0 +1,118 @@
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    fs, io,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+///events that can change what a build sees
+const MASK: u32 = libc::IN_CLOSE_WRITE
+    | libc::IN_MODIFY
+    | libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO
+    | libc::IN_DELETE_SELF;
+
+///a single change reported by the kernel
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub mask: u32,
+}
+
+///recursive directory watcher on top of a raw inotify descriptor
+pub struct Inotify {
+    fd: i32,
+    dirs: HashMap<i32, PathBuf>,
+}
+
+impl Inotify {
+    pub fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { fd, dirs: HashMap::new() })
+    }
+
+    ///watch `dir` and every directory below it, except hidden ones and `target`
+    pub fn watch_tree(&mut self, dir: &Path) -> io::Result<()> {
+        let name = CString::new(dir.as_os_str().as_bytes())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains a nul byte"))?;
+        let wd = unsafe { libc::inotify_add_watch(self.fd, name.as_ptr(), MASK) };
+        if wd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        self.dirs.insert(wd, dir.to_path_buf());
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() && !skipped(&path) {
+                self.watch_tree(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    ///wait up to `timeout_ms` (forever if negative) for events to be readable
+    pub fn wait(&self, timeout_ms: i32) -> io::Result<bool> {
+        let mut pfd = libc::pollfd { fd: self.fd, events: libc::POLLIN, revents: 0 };
+        loop {
+            let n = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+            if n >= 0 {
+                return Ok(n > 0);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+
+    ///read the pending events, new directories are watched right away
+    pub fn read(&mut self) -> io::Result<Vec<Event>> {
+        // aligned for `inotify_event`
+        let mut buf = vec![0u32; 4096 / 4];
+        let n = unsafe { libc::read(self.fd, buf.as_mut_ptr().cast(), buf.len() * 4) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), n as usize) };
+
+        let head = std::mem::size_of::<libc::inotify_event>();
+        let mut events = Vec::new();
+        let mut off = 0;
+        while off + head <= bytes.len() {
+            let ev = unsafe { std::ptr::read_unaligned(bytes[off..].as_ptr().cast::<libc::inotify_event>()) };
+            let name = &bytes[off + head..off + head + ev.len as usize];
+            let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+            off += head + ev.len as usize;
+
+            let Some(dir) = self.dirs.get(&ev.wd) else { continue };
+            let path = dir.join(std::ffi::OsStr::from_bytes(name));
+            let is_dir = ev.mask & libc::IN_ISDIR != 0;
+            if is_dir && ev.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 && !skipped(&path) {
+                // a directory that vanishes again before it is watched is not an error
+                let _ = self.watch_tree(&path);
+            }
+            if ev.mask & libc::IN_IGNORED != 0 {
+                self.dirs.remove(&ev.wd);
+            }
+            events.push(Event { path, is_dir, mask: ev.mask });
+        }
+        Ok(events)
+    }
+}
+
+impl Drop for Inotify {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+///directories never worth watching: hidden ones and build output
+fn skipped(dir: &Path) -> bool {
+    dir.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.') || n == "target")
+}
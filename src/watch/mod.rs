@@ -0,0 +1,86 @@
+#[cfg(target_os = "linux")]
+pub mod inotify;
+
+use crate::cli::defs::{Exit, Opts};
+
+///quiet period that has to pass after a change before rebuilding
+pub const DEBOUNCE_MS: i32 = 150;
+
+///`sip watch`: build, then rebuild every time a source file or manifest changes
+#[cfg(target_os = "linux")]
+pub fn watch(opts: &Opts) -> Exit {
+    use std::io::IsTerminal;
+
+    use crate::{driver::{EXT, build::{check, setup}}, manifest::MANIFEST};
+    use inotify::{Event, Inotify};
+
+    let relevant = |e: &Event| {
+        e.is_dir || e.path.extension().is_some_and(|x| x == EXT) || e.path.file_name().is_some_and(|n| n == MANIFEST)
+    };
+    loop {
+        // set up again every round, the manifest or dependencies may have changed
+        let setup = match setup(opts) {
+            Ok(s) => Some(s),
+            // usage errors will not fix themselves
+            Err(Exit::Usage) => return Exit::Usage,
+            Err(_) => None,
+        };
+        let mut dirs = vec![std::path::PathBuf::from(".")];
+        if let Some(s) = &setup {
+            dirs = s.layout.pkgs.iter().map(|p| p.dir.clone()).collect();
+            dirs.extend(s.project.clone());
+        } else if let Some(p) = &opts.path {
+            dirs = vec![crate::driver::root_of(std::path::Path::new(p))];
+        }
+
+        // watch before building so changes made during the build are not lost
+        let mut ino = match Inotify::new() {
+            Ok(i) => i,
+            Err(e) => {
+                eprintln!("error starting the file watcher!\nerr => {}", e);
+                return Exit::Io;
+            }
+        };
+        for d in &dirs {
+            if let Err(e) = ino.watch_tree(d) {
+                eprintln!("error watching '{}'!\nerr => {}", d.display(), e);
+                return Exit::Io;
+            }
+        }
+
+        if std::io::stdout().is_terminal() {
+            // clear the screen and move the cursor home
+            print!("\x1b[2J\x1b[H");
+        }
+        if let Some(s) = &setup {
+            let code = check(s);
+            if !opts.quiet {
+                let status = if code == Exit::Ok { "no errors" } else { "errors found" };
+                println!("[watch] {}, waiting for changes...", status);
+            }
+        }
+
+        // wait for a relevant change, then for things to settle down
+        let mut changed = false;
+        while !changed {
+            match ino.wait(-1).and_then(|_| ino.read()) {
+                Ok(evs) => changed = evs.iter().any(relevant),
+                Err(e) => {
+                    eprintln!("error reading file events!\nerr => {}", e);
+                    return Exit::Io;
+                }
+            }
+        }
+        while let Ok(true) = ino.wait(DEBOUNCE_MS) {
+            if ino.read().is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn watch(_opts: &Opts) -> Exit {
+    eprintln!("Error: `sip watch` needs inotify and is only supported on Linux.");
+    Exit::Usage
+}
@@ -0,0 +1,63 @@
+//! golden files for `sip build --emit=tokens|ast`, in tree and `--json` form
+//!
+//! after an intended change to the output, rewrite them with
+//! `SIP_BLESS=1 cargo test --test emit` and review the diff
+
+use std::{fs, path::Path, process::Command};
+
+///run `--emit=<emit>` on `tests/fixtures/emit/<name>.sip` and compare stdout with its golden file
+fn golden(name: &str, emit: &str, json: bool) -> std::process::Output {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let src = format!("tests/fixtures/emit/{}.sip", name);
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_sip"));
+    cmd.current_dir(root).args(["build", &src, &format!("--emit={}", emit), "--color=never"]);
+    if json {
+        cmd.arg("--json");
+    }
+    let out = cmd.output().expect("cannot run sip");
+    // the version changes with every release, the format should not
+    let got = String::from_utf8(out.stdout.clone()).unwrap().replace(env!("CARGO_PKG_VERSION"), "{version}");
+
+    let file = root.join(format!("tests/fixtures/emit/{}.{}.{}", name, emit, if json { "json" } else { "txt" }));
+    if std::env::var_os("SIP_BLESS").is_none() {
+        let want = fs::read_to_string(&file).unwrap_or_else(|e| panic!("{}: {}", file.display(), e));
+        assert!(got == want, "--emit={}{} changed, compare with {}:\n{}", emit, if json { " --json" } else { "" }, file.display(), got);
+    } else {
+        fs::write(&file, &got).unwrap();
+    }
+    out
+}
+
+fn clean(emit: &str, json: bool) {
+    let out = golden("main", emit, json);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+}
+
+#[test]
+fn tokens_tree() {
+    clean("tokens", false);
+}
+
+#[test]
+fn tokens_json() {
+    clean("tokens", true);
+}
+
+#[test]
+fn ast_tree() {
+    clean("ast", false);
+}
+
+#[test]
+fn ast_json() {
+    clean("ast", true);
+}
+
+#[test]
+fn errors_stay_off_stdout() {
+    let out = golden("errors", "ast", true);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(sip::json::Json::parse(&String::from_utf8_lossy(&out.stdout)).is_ok());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("[ERROR]"), "{}", stderr);
+}
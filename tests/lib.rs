@@ -0,0 +1,24 @@
+//! the front end as a library user sees it, on input an editor or a network hands over
+
+use sip::{ErrT, TT};
+
+#[test]
+fn invalid_utf8_is_an_error() {
+    let p = sip::parse(b"fn main() @ i32 {\n    \xff 1\n}\n");
+    assert!(p.get_ast().is_empty());
+    assert_eq!(p.errs.len(), 1, "{:?}", p.errs);
+    let e = &p.errs[0];
+    assert!(matches!(e.et, ErrT::SyntaxErr(m) if m.contains("UTF-8")), "{:?}", e);
+    assert_eq!((e.line, e.start, e.end), (2, 22, 23));
+}
+
+#[test]
+fn unterminated_string_runs_to_the_end() {
+    let src = "fn main() @ i32 { \"abc";
+    let toks = sip::lex(src);
+    let last = toks.last().unwrap();
+    assert_eq!(last.TT, TT::Unterminated);
+    assert_eq!((last.start, last.end), (18, src.len()));
+    let p = sip::parse(src);
+    assert!(p.errs.iter().any(|e| matches!(e.et, ErrT::SyntaxErr("unterminated string")) && e.start == 18), "{:?}", p.errs);
+}